@@ -1,12 +1,15 @@
 use crate::utility::{
     rand,
     vec3::{self, Vec3, Mul},
-    ray::Ray,
+    ray::{Differentials, Ray},
     color::{self, Color},
     interval::Interval,
+    spectrum::Wavelengths,
     INFINITY
 };
 use crate::hittable::*;
+use crate::material::medium_stack::MediumStack;
+use std::sync::Arc;
 use indicatif::ProgressBar;
 use rayon::prelude::*;
 
@@ -22,6 +25,8 @@ pub struct Camera {
     pub vup: Vec3,
     pub defocus_angle: f64,
     pub focus_dist: f64,
+    pub spectral: bool, // Trace sampled wavelengths instead of RGB
+    pub lights: Option<Arc<dyn Hittable>>, // Emitters to sample directly at each bounce
     image_height: i32,
     center: Vec3,
     pixel00_loc: Vec3,
@@ -35,14 +40,14 @@ pub struct Camera {
 }
 
 impl Camera {
-    pub fn render(&mut self, world:&(impl Hittable + Sync)) {
+    pub fn render(&mut self, world:&impl Hittable) {
         self.initialize();
 
         println!("P3"); // The colors are in ASCII
         println!("{} {}", self.image_width, self.image_height); // specifying number of columns and rows
         println!("255");
 
-        let pixel_number = (self.image_height * self.image_width);
+        let pixel_number = self.image_height * self.image_width;
 
         let bar = ProgressBar::new((pixel_number) as u64);
         let mut pixels: Vec<Color> = vec![Color::default()];
@@ -54,7 +59,13 @@ impl Camera {
             let mut pixel_color = Vec3::new(0.0, 0.0, 0.0);
             for _sample in 0..self.samples_per_pixel {
                 let r = self.get_ray(i,j);
-                pixel_color = pixel_color + self.ray_color(&r, self.max_depth, world);
+                pixel_color = pixel_color + if self.spectral {
+                    let wavelengths = Wavelengths::sample();
+                    let radiance = self.ray_color(&r, self.max_depth, world, &MediumStack::default(), Some(wavelengths), None);
+                    wavelengths.to_rgb(radiance)
+                } else {
+                    self.ray_color(&r, self.max_depth, world, &MediumStack::default(), None, None)
+                };
             }
 
             bar.inc(1);
@@ -64,7 +75,7 @@ impl Camera {
         let write_bar = ProgressBar::new(pixel_number as u64);
         for c in pixels {
             color::write_color(c, self.samples_per_pixel);
-            bar.inc(1);
+            write_bar.inc(1);
         }
 
 /*         for n in 0..(self.image_height * self.image_width) {
@@ -124,7 +135,16 @@ impl Camera {
     }
 
 
-    fn ray_color (&self, r: &Ray, depth:i32, world:&(impl Hittable + Sync)) -> Color {
+    fn ray_color (&self, r: &Ray, depth:i32, world:&impl Hittable, media:&MediumStack, wavelengths:Option<Wavelengths>, last_bounce:Option<(Vec3, f64)>) -> Color {
+        // In spectral mode the returned "color" holds the radiance at each of the sampled
+        // wavelengths, and every RGB quantity is converted to a spectrum on the way in.
+        // last_bounce is where the path last scattered off a material that light sampling also
+        // covers, with the density of the direction it picked.
+        let lift = |c: Color| match wavelengths {
+            None => c,
+            Some(w) => w.sample_rgb(c),
+        };
+
         let mut rec = HitRecord::default();
         
         // If we've exceeded the ray bounce limit, no more light is gathered.
@@ -132,24 +152,118 @@ impl Camera {
 
         // If the ray hits nothing, return the background color.
         if !world.hit(r, Interval::new(0.001, INFINITY), &mut rec) {
-            return self.background
+            return lift(self.background)
         }
+
+        rec.wavelength = wavelengths.map(|w| w.hero());
+        rec.compute_differentials(r);
+
+        // Light is absorbed along the segment by the medium the ray travelled through, and in
+        // scattering media it may bounce off a particle before reaching the surface.
+        let ray_length = r.direction().norm2().sqrt();
+        let segment_length = rec.t * ray_length;
+        let (distance, segment_weight) = media.sample_distance(segment_length);
+        if distance < segment_length {
+            let scattered = Ray::new_timed(r.at(distance / ray_length), vec3::random_unit_vector(), r.time());
+            return lift(segment_weight).mul(self.ray_color(&scattered, depth-1, world, media, wavelengths, None))
+        }
+        let segment_transmittance = lift(segment_weight);
         
-        let mut scattered = Ray::default();
-        let mut attenuation = Color::default();
-        let color_from_emission = rec.mat().emitted(rec.u, rec.v, rec.p);
+        let mut next_wavelengths = wavelengths;
+        let mut dispersion_weight = Color::new(1.0, 1.0, 1.0);
+        let mat = rec.mat();
+        let mut inside = media.clone(); // The media on the far side of the surface
+        if let Some(interface) = mat.interface() {
+            let id = Arc::as_ptr(&mat) as *const () as usize;
+            let is_true_hit;
+            if rec.front_face {
+                inside = media.entered(id, interface);
+                is_true_hit = interface.priority >= media.priority();
+                rec.exterior_ir = media.ir(rec.wavelength);
+            } else {
+                inside = media.exited(id);
+                is_true_hit = interface.priority >= inside.priority();
+                rec.exterior_ir = inside.ir(rec.wavelength);
+            }
+
+            // A higher priority medium owns this region, so the surface is invisible here. The
+            // ray carries on unchanged, but it has still crossed into or out of the medium.
+            if !is_true_hit {
+                let continued = Ray::new_timed(rec.p, r.direction(), r.time()).with_differentials(r.differentials());
+                return segment_transmittance.mul(self.ray_color(&continued, depth-1, world, &inside, wavelengths, last_bounce));
+            }
+        }
 
-        if !rec.mat().scatter(r, &rec, &mut attenuation, &mut scattered) {
-            return color_from_emission
+        if let (Some(w), true) = (wavelengths, mat.is_dispersive()) {
+            let (terminated, weight) = w.terminate_secondary();
+            next_wavelengths = Some(terminated);
+            dispersion_weight = weight;
         }
-        let color_from_scatter = attenuation.mul(self.ray_color(&scattered, depth-1, world));
+
+        let mut scattered = Ray::default();
+        let mut attenuation = Color::default();
+        let color_from_emission = lift(mat.emitted(r, &rec))
+            * self.emission_weight(r, last_bounce);
+
+        let Some(scattering_pdf) = mat.scatter_sampled(r, &rec, &mut attenuation, &mut scattered) else {
+            return segment_transmittance.mul(color_from_emission)
+        };
+
+        let color_from_lights = self.sample_lights(r, &rec, world, media, &lift).mul(dispersion_weight);
+
+        let scattered_differentials = rec.scattered_differentials(scattered.direction());
+        let scattered = scattered.with_differentials(scattered_differentials);
+
+        // Only rays that crossed the surface change media.
+        let transmitted = scattered.direction().dot(rec.normal) < 0.0;
+        let next_media = if transmitted {&inside} else {media};
+        // Delta lobes report no density, so light they find keeps its full weight.
+        let bounce = if scattering_pdf > 0.0 {Some((rec.p, scattering_pdf))} else {None};
+        let color_from_scatter = lift(attenuation).mul(dispersion_weight).mul(
+            self.ray_color(&scattered, depth-1, world, next_media, next_wavelengths, bounce)
+        );
         
-        color_from_emission + color_from_scatter
+        segment_transmittance.mul(color_from_emission + color_from_lights + color_from_scatter)
         /* let unit_direction = r.direction() / (r.direction().norm2().sqrt());
         let a = (unit_direction.y + 1.0) * 0.5;
         return Color::new(1.0, 1.0, 1.0)*(1.0 - a) + Color::new(0.5, 0.7, 1.0)*a; */
     }
 
+    fn emission_weight(&self, r: &Ray, last_bounce: Option<(Vec3, f64)>) -> f64 {
+        // Multiple importance sampling: light that sample_lights could also have found is
+        // shared between the two strategies with the power heuristic.
+        let (Some(lights), Some((origin, scattering_pdf))) = (&self.lights, last_bounce) else {return 1.0};
+        let light_pdf = lights.pdf_value(origin, r.direction());
+        scattering_pdf * scattering_pdf / (scattering_pdf * scattering_pdf + light_pdf * light_pdf)
+    }
+
+    fn sample_lights(&self, r: &Ray, rec: &HitRecord, world: &impl Hittable, media: &MediumStack, lift: &impl Fn(Color) -> Color) -> Color {
+        // Direct lighting: pick a point on one of the lights and see whether it is visible.
+        let Some(lights) = &self.lights else {return Color::default()};
+        let mat = rec.mat();
+
+        let light_ray = Ray::new_timed(rec.p, lights.random(rec.p), r.time());
+        let light_pdf = lights.pdf_value(rec.p, light_ray.direction());
+        if light_pdf <= 0.0 {return Color::default()}
+
+        let f = mat.eval(r, rec, &light_ray);
+        if f.x <= 0.0 && f.y <= 0.0 && f.z <= 0.0 {return Color::default()}
+
+        // Anything in the way, including a particle of a medium, blocks the light.
+        let mut light_rec = HitRecord::default();
+        if !world.hit(&light_ray, Interval::new(0.001, INFINITY), &mut light_rec) {
+            return Color::default()
+        }
+        light_rec.wavelength = rec.wavelength;
+        let emitted = light_rec.mat().emitted(&light_ray, &light_rec);
+        if emitted.x <= 0.0 && emitted.y <= 0.0 && emitted.z <= 0.0 {return Color::default()}
+
+        let scattering_pdf = mat.scattering_pdf(r, rec, &light_ray);
+        let weight = light_pdf * light_pdf / (light_pdf * light_pdf + scattering_pdf * scattering_pdf);
+        let transmittance = media.transmittance(light_rec.t * light_ray.direction().norm2().sqrt());
+        lift(f).mul(lift(emitted)).mul(lift(transmittance)) * (weight / light_pdf)
+    }
+
     fn get_ray(&self, i: i32, j:i32) -> Ray {
         // Get a randomly sampled camera ray for the pixel at location i,j originnating from
         // the camera defocus disk.
@@ -161,7 +275,16 @@ impl Camera {
         let ray_direction = pixel_sample - ray_origin;
         let ray_time = rand::random_double();
 
-        Ray::new_timed(ray_origin, ray_direction, ray_time)
+        // Offset rays one pixel over, narrowed as more samples share the pixel.
+        let spacing = (1.0 / (self.samples_per_pixel as f64).sqrt()).max(0.125);
+        let differentials = Differentials{
+            rx_origin: ray_origin,
+            rx_direction: ray_direction + self.pixel_delta_u * spacing,
+            ry_origin: ray_origin,
+            ry_direction: ray_direction + self.pixel_delta_v * spacing,
+        };
+
+        Ray::new_timed(ray_origin, ray_direction, ray_time).with_differentials(Some(differentials))
     }
 
     fn defocus_disk_sample(&self) -> Vec3 {
@@ -193,6 +316,8 @@ impl Default for Camera {
             vup: Vec3::new(0.0, 1.0, 0.0),
             defocus_angle: 0.0,
             focus_dist: 10.0,
+            spectral: false,
+            lights: None,
             image_height:0,
             center:Vec3::default(),
             pixel00_loc:Vec3::default(),
@@ -7,13 +7,16 @@ pub mod quad;
 pub mod translate;
 pub mod rotate_y;
 pub mod constant_medium;
+pub mod heterogeneous_medium;
+pub mod triangle;
+pub mod alpha_mask;
 
-use crate:: utility::{vec3::Vec3, ray::Ray, interval::Interval};
+use crate:: utility::{vec3::Vec3, ray::{Differentials, Ray}, interval::Interval};
 use crate::material::Material;
 use aabb::Aabb;
 use std::sync::Arc;
 
-#[derive(Default, Clone)]
+#[derive(Clone)]
 pub struct HitRecord {
     pub p: Vec3,
     pub normal: Vec3,
@@ -22,6 +25,44 @@ pub struct HitRecord {
     pub front_face: bool,
     pub u: f64,
     pub v: f64,
+    pub exterior_ir: f64, // Index of refraction on the other side of a dielectric interface
+    pub wavelength: Option<f64>, // Hero wavelength of the path in nanometers, in spectral mode
+    pub dpdu: Vec3, // How the hit point moves with the texture coordinates, zero if unknown
+    pub dpdv: Vec3,
+    pub object_p: Vec3, // The hit point and outward normal before any instance transforms
+    pub object_normal: Vec3,
+    pub differentials: Option<SurfaceDifferentials>,
+}
+
+// How the hit point and its texture coordinates change from one pixel to the next, for
+// filtering textures over the patch a ray covers.
+#[derive(Clone, Copy)]
+pub struct SurfaceDifferentials {
+    pub dpdx: Vec3,
+    pub dpdy: Vec3,
+    pub duvdx: (f64, f64),
+    pub duvdy: (f64, f64),
+}
+
+impl Default for HitRecord {
+    fn default() -> Self {
+        HitRecord {
+            p: Vec3::default(),
+            normal: Vec3::default(),
+            mat: None,
+            t: 0.0,
+            front_face: false,
+            u: 0.0,
+            v: 0.0,
+            exterior_ir: 1.0,
+            wavelength: None,
+            dpdu: Vec3::default(),
+            dpdv: Vec3::default(),
+            object_p: Vec3::default(),
+            object_normal: Vec3::default(),
+            differentials: None,
+        }
+    }
 }
 
 
@@ -38,6 +79,48 @@ impl HitRecord {
         }
     }
 
+    pub fn compute_differentials(&mut self, r: &Ray) {
+        // Intersect the offset rays with the tangent plane at the hit point, then express the
+        // offsets in terms of the texture coordinates (Igehy 1999, as in pbrt).
+        self.differentials = None;
+        let Some(d) = r.differentials() else {return};
+
+        let n = self.normal;
+        let plane = n.dot(self.p);
+        let offset = |origin: Vec3, direction: Vec3| -> Option<Vec3> {
+            let denom = n.dot(direction);
+            if denom.abs() < 1e-12 {return None}
+            let t = (plane - n.dot(origin)) / denom;
+            Some(origin + direction * t - self.p)
+        };
+        let (Some(dpdx), Some(dpdy)) = (offset(d.rx_origin, d.rx_direction), offset(d.ry_origin, d.ry_direction)) else {
+            return
+        };
+
+        // Least squares solution of dp = dpdu * du + dpdv * dv.
+        let (a, b, c) = (self.dpdu.dot(self.dpdu), self.dpdu.dot(self.dpdv), self.dpdv.dot(self.dpdv));
+        let det = a * c - b * b;
+        let solve = |dp: Vec3| -> (f64, f64) {
+            if det.abs() < 1e-20 {return (0.0, 0.0)}
+            let (pu, pv) = (self.dpdu.dot(dp), self.dpdv.dot(dp));
+            ((c * pu - b * pv) / det, (a * pv - b * pu) / det)
+        };
+
+        self.differentials = Some(SurfaceDifferentials{dpdx, dpdy, duvdx: solve(dpdx), duvdy: solve(dpdy)});
+    }
+
+    pub fn scattered_differentials(&self, direction: Vec3) -> Option<Differentials> {
+        // Keep the footprint of the incoming ray for the scattered one, as if the offset rays
+        // left the surface in parallel. This is exact for flat mirrors and keeps texture lookups
+        // after rougher bounces from growing blurry.
+        self.differentials.map(|d| Differentials{
+            rx_origin: self.p + d.dpdx,
+            rx_direction: direction,
+            ry_origin: self.p + d.dpdy,
+            ry_direction: direction,
+        })
+    }
+
     pub fn mat(&self) -> Arc<dyn Material>{
         self.mat.clone().unwrap()
     }
@@ -48,4 +131,20 @@ impl HitRecord {
 pub trait Hittable: Sync + Send{
     fn hit(&self, r: &Ray, ray_t: Interval, rec: &mut HitRecord) -> bool;
     fn bounding_box(&self) -> Aabb;
+
+    fn pdf_value(&self, _origin: Vec3, _direction: Vec3) -> f64 {
+        // Solid angle density with which random() picks the given direction towards this
+        // object. Objects that cannot be sampled as lights keep the default of zero.
+        0.0
+    }
+
+    fn random(&self, _origin: Vec3) -> Vec3 {
+        // A direction from origin towards a random point on this object.
+        Vec3::new(1.0, 0.0, 0.0)
+    }
+
+    fn area(&self) -> f64 {
+        // Surface area, for emitters specified by their total power. Volumes have none.
+        0.0
+    }
 }
\ No newline at end of file
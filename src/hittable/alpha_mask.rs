@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use crate::{texture::Texture, utility::{interval::Interval, rand, ray::Ray, vec3::Vec3}};
+
+use super::{aabb::Aabb, HitRecord, Hittable};
+
+enum Cutout {
+    Threshold(f64), // Opaque where alpha reaches the threshold, see-through elsewhere
+    Stochastic, // Opaque for a fraction alpha of the rays, so partial alpha blends smoothly
+}
+
+pub struct AlphaMask {
+    // Makes parts of a primitive transparent, as if they had been cut away: where the opacity
+    // texture (red channel) says so, rays pass straight through to whatever lies behind. Since
+    // it works at the intersection level, the holes show up in shadows too.
+    object: Arc<dyn Hittable>,
+    alpha: Arc<dyn Texture>,
+    cutout: Cutout,
+}
+
+impl AlphaMask {
+    pub fn new(object: Arc<dyn Hittable>, alpha: Arc<dyn Texture>) -> Self {
+        AlphaMask { object, alpha, cutout: Cutout::Threshold(0.5) }
+    }
+
+    #[allow(unused)]
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.cutout = Cutout::Threshold(threshold);
+        self
+    }
+
+    pub fn stochastic(mut self) -> Self {
+        self.cutout = Cutout::Stochastic;
+        self
+    }
+
+    fn is_opaque(&self, rec: &HitRecord) -> bool {
+        let alpha = self.alpha.value_at(rec).x;
+        match self.cutout {
+            Cutout::Threshold(threshold) => alpha >= threshold,
+            Cutout::Stochastic => rand::random_double() < alpha,
+        }
+    }
+}
+
+impl Hittable for AlphaMask {
+    fn hit(&self, r: &Ray, ray_t: Interval, rec: &mut HitRecord) -> bool {
+        // Skip over transparent hits until an opaque one turns up or the primitive runs out.
+        // Candidates go into a record of our own, since callers such as BVHNode keep the
+        // closest hit so far in rec and only expect it to change when we return true.
+        let mut interval = ray_t;
+        let mut candidate = HitRecord::default();
+        while self.object.hit(r, interval, &mut candidate) {
+            if self.is_opaque(&candidate) {
+                *rec = candidate;
+                return true
+            }
+            interval = Interval::new(candidate.t + 0.0001, interval.max);
+        }
+        false
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.object.bounding_box()
+    }
+
+    fn pdf_value(&self, origin: Vec3, direction: Vec3) -> f64 {
+        // Lights are sampled over their full extent; samples landing in a hole see past it and
+        // simply find no light there.
+        self.object.pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: Vec3) -> Vec3 {
+        self.object.random(origin)
+    }
+}
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use crate::{material::{isotropic::Isotropic, Material}, texture::Texture, utility::{color::Color, interval::Interval, rand, vec3::Vec3}};
+use crate::{material::{isotropic::Isotropic, volume_emission::VolumeEmission, Material}, texture::{solid_color::SolidColor, Texture}, utility::{color::Color, interval::Interval, rand, ray::Ray, vec3::Vec3}};
 
 use super::{HitRecord, Hittable};
 
@@ -20,6 +20,15 @@ impl ConstantMedium {
         }
     }
 
+    pub fn with_phase_function(b:Arc<dyn Hittable>, d:f64, phase_function:Arc<dyn Material>) -> Self {
+        // Use e.g. HenyeyGreenstein for media that scatter mostly forward or backward.
+        ConstantMedium{
+            boundary:b,
+            neg_inv_density:-1.0/d,
+            phase_function,
+        }
+    }
+
     pub fn from_color(b:Arc<dyn Hittable>, d:f64, c:Color) -> Self {
         ConstantMedium{
             boundary:b,
@@ -27,61 +36,196 @@ impl ConstantMedium {
             phase_function:Arc::new(Isotropic::from_color(c)),
         }
     }
+
+    pub fn with_emission(mut self, emission:Arc<dyn Texture>) -> Self {
+        // Make the medium glow, emitting the given radiance per unit length.
+        self.phase_function = Arc::new(VolumeEmission::new(
+            self.phase_function,
+            emission,
+            Arc::new(SolidColor::from_value(1.0)),
+            -1.0 / self.neg_inv_density,
+        ));
+        self
+    }
+
+    pub fn boundary_segments<'a>(boundary: &'a dyn Hittable, r: &'a Ray, ray_t: Interval) -> BoundarySegments<'a> {
+        // The parts of the ray, within ray_t, that lie inside the boundary.
+        BoundarySegments{boundary, r, ray_t, t: f64::NEG_INFINITY, inside: false}
+    }
+
+    pub fn free_flight_distance(neg_inv_density: f64) -> f64 {
+        // Sample how far light travels through a homogeneous medium before it scatters.
+        neg_inv_density * rand::random_double().ln()
+    }
 }
 
 impl Hittable for ConstantMedium {
     fn hit(&self, r: &crate::utility::ray::Ray, ray_t: crate::utility::interval::Interval, rec: &mut super::HitRecord) -> bool {
-        // Print occasional samples when devugging. To enable, set enableDebug true.
-        let enable_debug = false;
-        let debugging = enable_debug && rand::random_double() < 0.00001;
+        // Sample one free-flight distance, then spend it across the segments inside the
+        // boundary in order. Where it runs out, the ray scatters.
+        let ray_length = r.direction().norm2().sqrt();
+        let mut hit_distance = ConstantMedium::free_flight_distance(self.neg_inv_density);
+
+        for segment in ConstantMedium::boundary_segments(self.boundary.as_ref(), r, ray_t) {
+            let distance_inside_segment = segment.size() * ray_length;
+            if hit_distance > distance_inside_segment {
+                hit_distance -= distance_inside_segment;
+                continue
+            }
+
+            rec.t = segment.min + hit_distance / ray_length;
+            rec.p = r.at(rec.t);
+            rec.normal = Vec3::new(1.0,0.0,0.0);
+            (rec.object_p, rec.object_normal) = (rec.p, rec.normal);
+            rec.front_face = true;
+            rec.mat = Option::Some(self.phase_function.clone());
+            return true
+        }
 
-        let mut rec1 = HitRecord::default();
-        let mut rec2 = HitRecord::default();
+        false
+    }
 
-        if !self.boundary.hit(r, Interval::UNIVERSE, &mut rec1) {
-            return false
-        }
+    fn bounding_box(&self) -> super::aabb::Aabb {
+        self.boundary.bounding_box()
+    }
+}
+pub struct BoundarySegments<'a> {
+    // Walks the boundary crossings along a ray. Inside and outside alternate at each crossing,
+    // counted from the far side of the ray origin, so this works for non-convex boundaries,
+    // boundaries with holes, and rays that start inside.
+    boundary: &'a dyn Hittable,
+    r: &'a Ray,
+    ray_t: Interval,
+    t: f64,
+    inside: bool,
+}
 
-        if !self.boundary.hit(r, Interval::new(rec1.t+0.0001, f64::INFINITY), &mut rec2) {
-            return false
+impl Iterator for BoundarySegments<'_> {
+    type Item = Interval;
+
+    fn next(&mut self) -> Option<Interval> {
+        loop {
+            if self.t >= self.ray_t.max {
+                return None
+            }
+
+            let mut rec = HitRecord::default();
+            let crossing = if self.boundary.hit(self.r, Interval::new(self.t + 0.0001, f64::INFINITY), &mut rec) {
+                rec.t
+            } else if self.inside {
+                f64::INFINITY // The boundary is not closed, so stay inside
+            } else {
+                return None
+            };
+
+            let segment = Interval::new(self.t.max(self.ray_t.min), crossing.min(self.ray_t.max));
+            let was_inside = self.inside;
+            self.t = crossing;
+            self.inside = !self.inside;
+
+            if was_inside && segment.min < segment.max {
+                return Some(segment)
+            }
         }
+    }
+}
 
-        if debugging {eprintln!("\nray_tmin={}, ray_tmax={}\n",rec1.t,rec2.t);}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::{heterogeneous_medium::HeterogeneousMedium, hittable_list::HittableList, sphere::Sphere};
+    use crate::material::diffuse::Lambertian;
 
-        if rec1.t < ray_t.min {rec1.t = ray_t.min;}
-        if rec2.t > ray_t.max {rec2.t = ray_t.max;}
+    const SAMPLES: usize = 40000;
+    const DENSITY: f64 = 0.5;
 
-        if rec1.t >= rec2.t {
-            return false
-        }
+    fn sphere(center: Vec3, radius: f64) -> Arc<dyn Hittable> {
+        Arc::new(Sphere::new(center, radius, Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)))))
+    }
 
-        if rec1.t < 0.0 {
-            rec1.t = 0.0;
-        }
+    fn ray(origin: Vec3, direction: Vec3) -> Ray {
+        Ray::new_timed(origin, direction, 0.0)
+    }
 
-        let ray_length = r.direction().norm2().sqrt();
-        let distance_inside_boundary = (rec2.t - rec1.t) * ray_length;
-        let hit_distance = self.neg_inv_density * rand::random_double().ln();
+    fn transmittance(medium: &dyn Hittable, r: &Ray) -> f64 {
+        // Fraction of rays that pass through the medium without scattering.
+        let misses = (0..SAMPLES)
+            .filter(|_| !medium.hit(r, Interval::new(0.001, f64::INFINITY), &mut HitRecord::default()))
+            .count();
+        misses as f64 / SAMPLES as f64
+    }
 
-        if hit_distance > distance_inside_boundary {
-            return false
-        }
+    fn assert_transmittance(boundary: Arc<dyn Hittable>, r: Ray, length_inside: f64) {
+        let expected = (-DENSITY * length_inside).exp();
 
-        rec.t = rec1.t + hit_distance / ray_length;
-        rec.p = r.at(rec.t);
+        let constant = ConstantMedium::from_color(boundary.clone(), DENSITY, Color::new(1.0, 1.0, 1.0));
+        let estimate = transmittance(&constant, &r);
+        assert!((estimate - expected).abs() < 0.015, "constant medium: expected {expected}, got {estimate}");
 
-        if debugging {
-            eprintln!("hit_distance = {}\nrec.t = {}\nrec.p = {}",hit_distance, rec.t, rec.p);
-        }
+        // Half the majorant, so delta tracking rejects about half of its collisions.
+        let heterogeneous = HeterogeneousMedium::from_color(
+            boundary, Arc::new(SolidColor::from_value(0.5)), 2.0 * DENSITY, Color::new(1.0, 1.0, 1.0)
+        );
+        let estimate = transmittance(&heterogeneous, &r);
+        assert!((estimate - expected).abs() < 0.015, "heterogeneous medium: expected {expected}, got {estimate}");
+    }
+
+    #[test]
+    fn convex_boundary() {
+        let r = ray(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert_transmittance(sphere(Vec3::new(0.0, 0.0, 0.0), 1.0), r, 2.0);
+    }
 
-        rec.normal = Vec3::new(1.0,0.0,0.0);
-        rec.front_face = true;
-        rec.mat = Option::Some(self.phase_function.clone());
+    #[test]
+    fn unnormalized_ray_direction() {
+        let r = ray(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 3.0));
+        assert_transmittance(sphere(Vec3::new(0.0, 0.0, 0.0), 1.0), r, 2.0);
+    }
 
-        true
+    #[test]
+    fn disjoint_boundaries() {
+        let mut boundary = HittableList::default();
+        boundary.add(sphere(Vec3::new(0.0, 0.0, 0.0), 1.0));
+        boundary.add(sphere(Vec3::new(0.0, 0.0, 4.0), 0.5));
+        let r = ray(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert_transmittance(Arc::new(boundary), r, 3.0);
     }
 
-    fn bounding_box(&self) -> super::aabb::Aabb {
-        self.boundary.bounding_box()
+    #[test]
+    fn boundary_with_hole() {
+        // A spherical shell: the inner sphere bounds a cavity with no medium.
+        let mut boundary = HittableList::default();
+        boundary.add(sphere(Vec3::new(0.0, 0.0, 0.0), 2.0));
+        boundary.add(sphere(Vec3::new(0.0, 0.0, 0.0), 1.0));
+        let r = ray(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        assert_transmittance(Arc::new(boundary), r, 2.0);
+    }
+
+    #[test]
+    fn ray_starting_inside() {
+        let r = ray(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+        assert_transmittance(sphere(Vec3::new(0.0, 0.0, 0.0), 1.5), r, 1.5);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn ray_starting_in_hole() {
+        let mut boundary = HittableList::default();
+        boundary.add(sphere(Vec3::new(0.0, 0.0, 0.0), 2.0));
+        boundary.add(sphere(Vec3::new(0.0, 0.0, 0.0), 1.0));
+        let r = ray(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        assert_transmittance(Arc::new(boundary), r, 1.0);
+    }
+
+    #[test]
+    fn ray_ending_inside() {
+        let boundary = sphere(Vec3::new(0.0, 0.0, 0.0), 1.0);
+        let r = ray(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+        let medium = ConstantMedium::from_color(boundary, DENSITY, Color::new(1.0, 1.0, 1.0));
+        let misses = (0..SAMPLES)
+            .filter(|_| !medium.hit(&r, Interval::new(0.001, 4.5), &mut HitRecord::default()))
+            .count();
+        let estimate = misses as f64 / SAMPLES as f64;
+        let expected = (-DENSITY * 0.5).exp();
+        assert!((estimate - expected).abs() < 0.015, "expected {expected}, got {estimate}");
+    }
+}
@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use crate::{material::{isotropic::Isotropic, volume_emission::VolumeEmission, Material}, texture::{voxel_grid::VoxelGrid, Texture}, utility::{color::Color, interval::Interval, rand, vec3::Vec3}};
+
+use super::{constant_medium::ConstantMedium, quad::_box, HitRecord, Hittable};
+
+pub struct HeterogeneousMedium {
+    boundary: Arc<dyn Hittable>,
+    density: Arc<dyn Texture>, // Fraction of max_density at each point, read from the red channel
+    max_density: f64,
+    phase_function: Arc<dyn Material>,
+}
+
+impl HeterogeneousMedium {
+    #[allow(unused)]
+    pub fn new(b:Arc<dyn Hittable>, density:Arc<dyn Texture>, max_density:f64, a:Arc<dyn Texture>) -> Self {
+        HeterogeneousMedium{
+            boundary:b,
+            density,
+            max_density,
+            phase_function:Arc::new(Isotropic::new(a)),
+        }
+    }
+
+    pub fn with_phase_function(b:Arc<dyn Hittable>, density:Arc<dyn Texture>, max_density:f64, phase_function:Arc<dyn Material>) -> Self {
+        HeterogeneousMedium{
+            boundary:b,
+            density,
+            max_density,
+            phase_function,
+        }
+    }
+
+    pub fn from_color(b:Arc<dyn Hittable>, density:Arc<dyn Texture>, max_density:f64, c:Color) -> Self {
+        HeterogeneousMedium{
+            boundary:b,
+            density,
+            max_density,
+            phase_function:Arc::new(Isotropic::from_color(c)),
+        }
+    }
+
+    pub fn from_voxel_grid(grid:Arc<VoxelGrid>, max_density:f64, c:Color) -> Self {
+        // The medium fills the grid's bounding box; the densest voxel has max_density.
+        let bounds = grid.bounds();
+        let phase_function: Arc<dyn Material> = Arc::new(Isotropic::from_color(c));
+        let boundary = _box(
+            Vec3::new(bounds.x.min, bounds.y.min, bounds.z.min),
+            Vec3::new(bounds.x.max, bounds.y.max, bounds.z.max),
+            phase_function.clone()
+        );
+        HeterogeneousMedium{
+            boundary,
+            density:grid,
+            max_density,
+            phase_function,
+        }
+    }
+
+    pub fn with_emission(mut self, emission:Arc<dyn Texture>) -> Self {
+        // Make the medium glow, emitting the given radiance per unit length. Emission only
+        // comes from where the density is nonzero.
+        self.phase_function = Arc::new(VolumeEmission::new(
+            self.phase_function,
+            emission,
+            self.density.clone(),
+            self.max_density,
+        ));
+        self
+    }
+
+    fn density_at(&self, p: Vec3) -> f64 {
+        // Points inside the volume have no surface coordinates, only a position.
+        let at = HitRecord { p, ..Default::default() };
+        self.max_density * self.density.value_at(&at).x.clamp(0.0, 1.0)
+    }
+}
+
+impl Hittable for HeterogeneousMedium {
+    fn hit(&self, r: &crate::utility::ray::Ray, ray_t: Interval, rec: &mut HitRecord) -> bool {
+        if self.max_density <= 0.0 {
+            return false
+        }
+
+        // Delta tracking: take free-flight steps through a homogeneous medium as dense as the
+        // densest point, and treat each collision as real with probability density / majorant.
+        // The rejected collisions are null, so the ray continues unchanged. Free flights are
+        // memoryless, so each segment inside the boundary can be tracked on its own.
+        let ray_length = r.direction().norm2().sqrt();
+        let neg_inv_majorant = -1.0 / self.max_density;
+
+        for segment in ConstantMedium::boundary_segments(self.boundary.as_ref(), r, ray_t) {
+            let mut t = segment.min;
+            loop {
+                t += ConstantMedium::free_flight_distance(neg_inv_majorant) / ray_length;
+                if t >= segment.max {
+                    break
+                }
+
+                let p = r.at(t);
+                if rand::random_double() * self.max_density < self.density_at(p) {
+                    rec.t = t;
+                    rec.p = p;
+                    rec.normal = Vec3::new(1.0,0.0,0.0);
+                    (rec.object_p, rec.object_normal) = (p, rec.normal);
+                    rec.front_face = true;
+                    rec.mat = Option::Some(self.phase_function.clone());
+                    return true
+                }
+            }
+        }
+
+        false
+    }
+
+    fn bounding_box(&self) -> super::aabb::Aabb {
+        self.boundary.bounding_box()
+    }
+}
@@ -1,4 +1,4 @@
-use crate::utility::{ray::Ray, interval::Interval};
+use crate::utility::{ray::Ray, interval::Interval, rand, vec3::Vec3};
 use std::{sync::Arc};
 use super::{Hittable, HitRecord, aabb::Aabb};
 
@@ -44,4 +44,21 @@ impl Hittable for HittableList {
     fn bounding_box(&self) -> Aabb {
         self.bbox
     }
+
+    fn pdf_value(&self, origin: Vec3, direction: Vec3) -> f64 {
+        // Each object is picked with equal probability.
+        if self.objects.is_empty() {return 0.0}
+        let weight = 1.0 / self.objects.len() as f64;
+        self.objects.iter().map(|object| weight * object.pdf_value(origin, direction)).sum()
+    }
+
+    fn random(&self, origin: Vec3) -> Vec3 {
+        if self.objects.is_empty() {return Vec3::new(1.0, 0.0, 0.0)}
+        let i = rand::random_int_range(0, self.objects.len() as i32) as usize;
+        self.objects[i].random(origin)
+    }
+
+    fn area(&self) -> f64 {
+        self.objects.iter().map(|object| object.area()).sum()
+    }
 }
\ No newline at end of file
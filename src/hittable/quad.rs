@@ -1,6 +1,6 @@
 use std::{sync::Arc};
 
-use crate::{material::{Mat, Material}, utility::{interval::Interval, ray::Ray, vec3::{self, Vec3}}};
+use crate::{material::{Mat, Material}, utility::{interval::Interval, rand, ray::Ray, vec3::{self, Vec3}}};
 
 use super::{aabb::Aabb, hittable_list::HittableList, HitRecord, Hittable};
 
@@ -14,6 +14,7 @@ pub struct Quad {
     normal:Vec3,
     d:f64,
     w:Vec3,
+    area:f64,
 }
 
 impl Quad {
@@ -22,7 +23,8 @@ impl Quad {
         let normal = vec3::unit_vector(n);
         let d = normal.dot(q);
         let w = n / n.norm2();
-        let mut q1 = Quad {q, u, v, mat, bbox:Aabb::default(), normal, d, w};
+        let area = n.norm2().sqrt();
+        let mut q1 = Quad {q, u, v, mat, bbox:Aabb::default(), normal, d, w, area};
         q1.set_bounding_box();
         q1
     }
@@ -49,6 +51,10 @@ impl Hittable for Quad {
         self.bbox
     }
 
+    fn area(&self) -> f64 {
+        self.area
+    }
+
     fn hit(&self, r: &Ray, ray_t: Interval, rec: &mut HitRecord) -> bool {
         let denom = self.normal.dot(r.direction());
 
@@ -71,13 +77,43 @@ impl Hittable for Quad {
         // Ray hits the 2D shape; set the rest of the hit record and return true.
         rec.t = t;
         rec.p = intersection;
+        rec.dpdu = self.u;
+        rec.dpdv = self.v;
+        rec.object_p = intersection;
+        rec.object_normal = self.normal;
         rec.mat = Option::Some(self.mat.clone());
         rec.set_face_normal(r, self.normal);
 
         true
     }
 
+    fn pdf_value(&self, origin: Vec3, direction: Vec3) -> f64 {
+        let mut rec = HitRecord::default();
+        if !self.hit(&Ray::new_timed(origin, direction, 0.0), Interval::new(0.001, f64::INFINITY), &mut rec) {
+            return 0.0
+        }
+
+        // Emitters with textured emission are sampled in proportion to their brightness.
+        let area_pdf = match self.mat.emission_distribution() {
+            Some(distribution) => distribution.pdf(rec.u, rec.v) / self.area,
+            None => 1.0 / self.area,
+        };
 
+        let distance_squared = rec.t * rec.t * direction.norm2();
+        let cosine = (direction.dot(self.normal) / direction.norm2().sqrt()).abs();
+        area_pdf * distance_squared / cosine
+    }
+
+    fn random(&self, origin: Vec3) -> Vec3 {
+        let (a, b) = match self.mat.emission_distribution() {
+            Some(distribution) => {
+                let (a, b, _) = distribution.sample(rand::random_double(), rand::random_double());
+                (a, b)
+            }
+            None => (rand::random_double(), rand::random_double()),
+        };
+        self.q + (self.u * a) + (self.v * b) - origin
+    }
 }
 
 pub fn _box(a:Vec3, b:Vec3, mat:Arc<dyn Material>) -> Arc<HittableList> {
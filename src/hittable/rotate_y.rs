@@ -50,6 +50,16 @@ impl RotateY {
     }
 }
 
+impl RotateY {
+    fn to_world(&self, a: Vec3) -> Vec3 {
+        Vec3::new(self.cos_theta*a.x + self.sin_theta*a.z, a.y, -self.sin_theta*a.x + self.cos_theta*a.z)
+    }
+
+    fn to_object(&self, a: Vec3) -> Vec3 {
+        Vec3::new(self.cos_theta*a.x - self.sin_theta*a.z, a.y, self.sin_theta*a.x + self.cos_theta*a.z)
+    }
+}
+
 impl Hittable for RotateY {
     fn hit(&self, r: &Ray, ray_t: Interval, rec: &mut HitRecord) -> bool {
         // Change the ray from the world space to object space
@@ -83,6 +93,8 @@ impl Hittable for RotateY {
 
         rec.p = p;
         rec.normal = normal;
+        rec.dpdu = self.to_world(rec.dpdu);
+        rec.dpdv = self.to_world(rec.dpdv);
 
         true
     }
@@ -90,5 +102,18 @@ impl Hittable for RotateY {
     fn bounding_box(&self) -> Aabb {
         self.bbox
     }
+
+    fn pdf_value(&self, origin: Vec3, direction: Vec3) -> f64 {
+        // A rotation preserves solid angles, so the object's density carries over as is.
+        self.object.pdf_value(self.to_object(origin), self.to_object(direction))
+    }
+
+    fn random(&self, origin: Vec3) -> Vec3 {
+        self.to_world(self.object.random(self.to_object(origin)))
+    }
+
+    fn area(&self) -> f64 {
+        self.object.area()
+    }
 }
 
@@ -1,4 +1,4 @@
-use crate::utility::{vec3::{self, Vec3}, interval::Interval, ray::Ray, PI};
+use crate::utility::{vec3::{self, Vec3}, interval::Interval, onb::Onb, rand, ray::Ray, PI};
 use super::{Hittable, HitRecord, aabb::Aabb};
 use crate::material::Mat;
 
@@ -55,6 +55,30 @@ impl Sphere {
         *u = phi / (2.0*PI);
         *v = theta / PI;
     }
+
+    fn uv_derivatives(&self, u: f64, v: f64) -> (Vec3, Vec3) {
+        // Derivatives of the point with the (u, v) of get_sphere_uv, where
+        // p = center + radius * (-sin(theta) cos(phi), -cos(theta), sin(theta) sin(phi)).
+        let (sin_theta, cos_theta) = (v * PI).sin_cos();
+        let (sin_phi, cos_phi) = (u * 2.0 * PI).sin_cos();
+        let dpdu = Vec3::new(sin_theta * sin_phi, 0.0, sin_theta * cos_phi) * (2.0 * PI * self.radius);
+        let dpdv = Vec3::new(-cos_theta * cos_phi, sin_theta, cos_theta * sin_phi) * (PI * self.radius);
+        (dpdu, dpdv)
+    }
+
+    fn random_to_sphere(radius: f64, distance_squared: f64) -> Vec3 {
+        // A direction uniformly distributed over the cone that a sphere subtends, with the
+        // cone axis along z.
+        let r1 = rand::random_double();
+        let r2 = rand::random_double();
+        let z = 1.0 + r2 * ((1.0 - radius * radius / distance_squared).sqrt() - 1.0);
+
+        let phi = 2.0 * PI * r1;
+        let x = phi.cos() * (1.0 - z * z).sqrt();
+        let y = phi.sin() * (1.0 - z * z).sqrt();
+
+        Vec3::new(x, y, z)
+    }
 }
 
 impl Hittable for Sphere {
@@ -85,6 +109,10 @@ impl Hittable for Sphere {
         let outward_normal = (rec.p - center) / self.radius;
         rec.set_face_normal(r, outward_normal);
         Sphere::get_sphere_uv(outward_normal, &mut rec.u, &mut rec.v);
+        (rec.dpdu, rec.dpdv) = self.uv_derivatives(rec.u, rec.v);
+        // A moving sphere carries its object space along with it.
+        rec.object_p = rec.p - (center - self.center1);
+        rec.object_normal = outward_normal;
         rec.mat = Option::Some(self.mat.clone());
 
         true
@@ -93,4 +121,32 @@ impl Hittable for Sphere {
     fn bounding_box(&self) -> Aabb {
         self.bbox
     }
+
+    fn area(&self) -> f64 {
+        4.0 * PI * self.radius * self.radius
+    }
+
+    fn pdf_value(&self, origin: Vec3, direction: Vec3) -> f64 {
+        // This method only works for stationary spheres seen from outside.
+        let distance_squared = (self.center1 - origin).norm2();
+        if self.is_moving || distance_squared <= self.radius * self.radius {
+            return 0.0
+        }
+
+        let mut rec = HitRecord::default();
+        if !self.hit(&Ray::new_timed(origin, direction, 0.0), Interval::new(0.001, f64::INFINITY), &mut rec) {
+            return 0.0
+        }
+
+        let cos_theta_max = (1.0 - self.radius * self.radius / distance_squared).sqrt();
+        let solid_angle = 2.0 * PI * (1.0 - cos_theta_max);
+        1.0 / solid_angle
+    }
+
+    fn random(&self, origin: Vec3) -> Vec3 {
+        let direction = self.center1 - origin;
+        let uvw = Onb::build_from_w(direction);
+        let local = Sphere::random_to_sphere(self.radius, direction.norm2());
+        uvw.local(local.x, local.y, local.z)
+    }
 }
\ No newline at end of file
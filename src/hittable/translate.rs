@@ -39,4 +39,17 @@ impl Hittable for Translate {
     fn bounding_box(&self) -> Aabb {
         self.bbox
     }
+
+    fn pdf_value(&self, origin: Vec3, direction: Vec3) -> f64 {
+        // Directions are unchanged by a translation, only the origin moves.
+        self.object.pdf_value(origin - self.offset, direction)
+    }
+
+    fn random(&self, origin: Vec3) -> Vec3 {
+        self.object.random(origin - self.offset)
+    }
+
+    fn area(&self) -> f64 {
+        self.object.area()
+    }
 }
\ No newline at end of file
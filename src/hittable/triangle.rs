@@ -0,0 +1,115 @@
+use crate::{material::Mat, utility::{interval::Interval, rand, ray::Ray, vec3::{self, Vec3}}};
+
+use super::{aabb::Aabb, HitRecord, Hittable};
+
+pub struct Triangle {
+    a: Vec3,
+    ab: Vec3,
+    ac: Vec3,
+    uvs: [(f64, f64); 3], // Texture coordinates at the three vertices
+    mat: Mat,
+    bbox: Aabb,
+    normal: Vec3,
+    area: f64,
+    dpdu: Vec3,
+    dpdv: Vec3,
+}
+
+impl Triangle {
+    #[allow(unused)]
+    pub fn new(a: Vec3, b: Vec3, c: Vec3, mat: Mat) -> Self {
+        // Without explicit texture coordinates the triangle maps onto half of the unit square,
+        // like a quad cut along its diagonal.
+        Triangle::with_uvs(a, b, c, [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)], mat)
+    }
+
+    pub fn with_uvs(a: Vec3, b: Vec3, c: Vec3, uvs: [(f64, f64); 3], mat: Mat) -> Self {
+        let ab = b - a;
+        let ac = c - a;
+        let n = ab.cross(ac);
+        let normal = vec3::unit_vector(n);
+        let area = 0.5 * n.norm2().sqrt();
+
+        // The texture coordinates vary linearly over the triangle, so the tangents are constant:
+        // solve ab = dpdu * du1 + dpdv * dv1 and ac = dpdu * du2 + dpdv * dv2.
+        let (du1, dv1) = (uvs[1].0 - uvs[0].0, uvs[1].1 - uvs[0].1);
+        let (du2, dv2) = (uvs[2].0 - uvs[0].0, uvs[2].1 - uvs[0].1);
+        let det = du1 * dv2 - dv1 * du2;
+        let (dpdu, dpdv) = if det.abs() < 1e-12 {
+            // Degenerate texture coordinates; any frame in the plane will do.
+            (ab, normal.cross(ab))
+        } else {
+            ((ab * dv2 - ac * dv1) / det, (ac * du1 - ab * du2) / det)
+        };
+
+        let min = Vec3::new(a.x.min(b.x).min(c.x), a.y.min(b.y).min(c.y), a.z.min(b.z).min(c.z));
+        let max = Vec3::new(a.x.max(b.x).max(c.x), a.y.max(b.y).max(c.y), a.z.max(b.z).max(c.z));
+
+        Triangle {a, ab, ac, uvs, mat, bbox: Aabb::from_points(min, max).pad(), normal, area, dpdu, dpdv}
+    }
+}
+
+impl Hittable for Triangle {
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    fn area(&self) -> f64 {
+        self.area
+    }
+
+    fn hit(&self, r: &Ray, ray_t: Interval, rec: &mut HitRecord) -> bool {
+        // Möller-Trumbore: solve for the hit distance and the barycentric coordinates at once.
+        let pvec = r.direction().cross(self.ac);
+        let det = self.ab.dot(pvec);
+
+        // No hit if the ray is parallel to the plane.
+        if det.abs() < 1e-12 {return false}
+        let inv_det = 1.0 / det;
+
+        let tvec = r.origin() - self.a;
+        let beta = tvec.dot(pvec) * inv_det;
+        if !(0.0..=1.0).contains(&beta) {return false}
+
+        let qvec = tvec.cross(self.ab);
+        let gamma = r.direction().dot(qvec) * inv_det;
+        if gamma < 0.0 || beta + gamma > 1.0 {return false}
+
+        let t = self.ac.dot(qvec) * inv_det;
+        if !ray_t.contains(t) {return false}
+
+        let alpha = 1.0 - beta - gamma;
+        rec.t = t;
+        rec.p = r.at(t);
+        rec.u = alpha * self.uvs[0].0 + beta * self.uvs[1].0 + gamma * self.uvs[2].0;
+        rec.v = alpha * self.uvs[0].1 + beta * self.uvs[1].1 + gamma * self.uvs[2].1;
+        rec.dpdu = self.dpdu;
+        rec.dpdv = self.dpdv;
+        rec.object_p = rec.p;
+        rec.object_normal = self.normal;
+        rec.mat = Option::Some(self.mat.clone());
+        rec.set_face_normal(r, self.normal);
+
+        true
+    }
+
+    fn pdf_value(&self, origin: Vec3, direction: Vec3) -> f64 {
+        let mut rec = HitRecord::default();
+        if !self.hit(&Ray::new_timed(origin, direction, 0.0), Interval::new(0.001, f64::INFINITY), &mut rec) {
+            return 0.0
+        }
+
+        let distance_squared = rec.t * rec.t * direction.norm2();
+        let cosine = (direction.dot(self.normal) / direction.norm2().sqrt()).abs();
+        distance_squared / (cosine * self.area)
+    }
+
+    fn random(&self, origin: Vec3) -> Vec3 {
+        // Fold points of the parallelogram that fall outside back onto the triangle.
+        let (mut a, mut b) = (rand::random_double(), rand::random_double());
+        if a + b > 1.0 {
+            (a, b) = (1.0 - a, 1.0 - b);
+        }
+        self.a + self.ab * a + self.ac * b - origin
+    }
+}
@@ -7,15 +7,25 @@ mod texture;
 
 use utility::{rand, color::Color, vec3::{self, Vec3, Mul}};
 use hittable::{
-    bvh::BVHNode, constant_medium::ConstantMedium, hittable_list::HittableList, quad::{Quad, _box}, rotate_y::RotateY, sphere::Sphere, translate::Translate, Hittable
+    aabb::Aabb, alpha_mask::AlphaMask, bvh::BVHNode, constant_medium::ConstantMedium, heterogeneous_medium::HeterogeneousMedium, hittable_list::HittableList, quad::{Quad, _box}, rotate_y::RotateY, sphere::Sphere, translate::Translate, triangle::Triangle, Hittable
 };
 use material::{
-    dielectric::Dielectric, diffuse::Lambertian, diffuse_light::DiffuseLight, metal::Metal, Material
+    dielectric::Dielectric, diffuse::Lambertian, ior::Ior, diffuse_light::{DiffuseLight, EmittingSides, Power}, ies::IesProfile, henyey_greenstein::HenyeyGreenstein, layered::Layered, metal::Metal, mix::MixMaterial, normal_map::NormalMap, principled::Principled, rough_dielectric::RoughDielectric, subsurface::Subsurface, thin_film::ThinFilm, Material
 };
 use texture::{
-    checkered::Checkered,
-    image::ImageTexture,
-    noise::NoiseTexture
+    checkered::{Checkered, UvCheckered},
+    mapping::{MappedTexture, Mapping},
+    image::{ColorSpace, Filter, ImageTexture, Wrap},
+    uv_transform::UvTransform,
+    noise::NoiseTexture,
+    color_ramp::ColorRamp,
+    procedural::{CellFeature, Cellular, DomainWarp, Fbm, Marble, Ridged, Wood},
+    node::{HsvAdjust, Lerp},
+    graph::TextureGraph,
+    voxel_grid::VoxelGrid,
+    blackbody::Blackbody,
+    solid_color::SolidColor,
+    Texture
 };
 use camera::Camera;
 use std::sync::Arc;
@@ -37,6 +47,29 @@ fn main() {
         "7" => cornell_box(),
         "8" => cornell_smoke(),
         "9" => final_scene(800, 10000, 40),
+        "10" => frosted_glass(),
+        "11" => colored_glass(),
+        "12" => nested_dielectrics(),
+        "13" => dispersion(),
+        "14" => principled_spheres(),
+        "15" => coated_spheres(),
+        "16" => mixed_materials(),
+        "17" => subsurface_spheres(),
+        "18" => thin_films(),
+        "19" => cornell_fog(),
+        "20" => heterogeneous_volumes(),
+        "21" => smoke_ring(),
+        "22" => glowing_volumes(),
+        "23" => blackbody_lights(),
+        "24" => ies_lights(),
+        "25" => emissive_panels(),
+        "26" => texture_filtering(),
+        "27" => data_textures(),
+        "28" => bump_mapping(),
+        "29" => cutouts(),
+        "30" => texture_mapping(),
+        "31" => procedural_textures(),
+        "32" => texture_graph(),
         _ => final_scene(400, 250, 10)
     };
 }
@@ -614,4 +647,1214 @@ fn final_scene(image_width:i32, samples_per_pixel:i32, max_depth:i32) {
 
 
 
-}
\ No newline at end of file
+}
+
+fn frosted_glass() {
+    let mut world = HittableList::default();
+
+    let checker = Arc::new(Checkered::from_colors(0.32, Color::new(0.2, 0.3, 0.1), Color::new(0.9, 0.9, 0.9)));
+    world.add(Arc::new(Sphere::new(
+        Vec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(checker))
+    )));
+
+    // A textured backdrop makes the blur through the rough glass easy to see.
+    let backdrop = Arc::new(Lambertian::from_texture(Arc::new(ImageTexture::new("earthmap.jpg"))));
+    world.add(Arc::new(Quad::new(
+        Vec3::new(-6.0, 0.0, -3.0),
+        Vec3::new(12.0, 0.0, 0.0),
+        Vec3::new(0.0, 6.0, 0.0),
+        backdrop
+    )));
+
+    // Smooth, lightly frosted and ground glass, left to right.
+    world.add(Arc::new(Sphere::new(Vec3::new(-2.2, 1.0, 0.0), 1.0, Arc::new(RoughDielectric::new(1.5, 0.02)))));
+    world.add(Arc::new(Sphere::new(Vec3::new( 0.0, 1.0, 0.0), 1.0, Arc::new(RoughDielectric::new(1.5, 0.2)))));
+    world.add(Arc::new(Sphere::new(Vec3::new( 2.2, 1.0, 0.0), 1.0, Arc::new(RoughDielectric::new(1.5, 0.5)))));
+
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 16.0 / 9.0;
+    cam.image_width = 400;
+    cam.samples_per_pixel = 200;
+    cam.max_depth = 50;
+    cam.background = Color::new(0.70, 0.80, 1.00);
+
+
+    cam.vfov = 30.0;
+    cam.lookfrom = Vec3::new(0.0, 2.0, 10.0);
+    cam.lookat = Vec3::new(0.0, 1.0, 0.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+}
+
+fn colored_glass() {
+    let mut world = HittableList::default();
+
+    let checker = Arc::new(Checkered::from_colors(0.32, Color::new(0.2, 0.3, 0.1), Color::new(0.9, 0.9, 0.9)));
+    world.add(Arc::new(Sphere::new(
+        Vec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(checker))
+    )));
+
+    // The same amber glass gets deeper in color as the path length through it grows.
+    let amber = Color::new(0.9, 0.6, 0.2);
+    world.add(Arc::new(Sphere::new(Vec3::new(-2.6, 0.3, 0.5), 0.3, Arc::new(Dielectric::from_transmission(1.5, amber, 1.0)))));
+    world.add(Arc::new(Sphere::new(Vec3::new(-1.4, 0.6, 0.0), 0.6, Arc::new(Dielectric::from_transmission(1.5, amber, 1.0)))));
+    world.add(Arc::new(Sphere::new(Vec3::new( 0.6, 1.0, 0.0), 1.0, Arc::new(Dielectric::from_transmission(1.5, amber, 1.0)))));
+    world.add(Arc::new(Sphere::new(
+        Vec3::new(2.8, 0.8, 0.0),
+        0.8,
+        Arc::new(RoughDielectric::from_transmission(1.5, 0.3, Color::new(0.3, 0.67, 0.9), 1.0))
+    )));
+
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 16.0 / 9.0;
+    cam.image_width = 400;
+    cam.samples_per_pixel = 200;
+    cam.max_depth = 50;
+    cam.background = Color::new(0.70, 0.80, 1.00);
+
+
+    cam.vfov = 30.0;
+    cam.lookfrom = Vec3::new(0.0, 2.0, 10.0);
+    cam.lookat = Vec3::new(0.0, 0.8, 0.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+}
+
+fn nested_dielectrics() {
+    let mut world = HittableList::default();
+
+    let checker = Arc::new(Checkered::from_colors(0.32, Color::new(0.2, 0.3, 0.1), Color::new(0.9, 0.9, 0.9)));
+    world.add(Arc::new(Sphere::new(
+        Vec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(checker))
+    )));
+
+    // Liquid in a glass: the liquid overlaps the inner wall of the hollow glass shell, and the
+    // glass has the higher priority so it owns the overlap.
+    let glass: Arc<dyn Material> = Arc::new(Dielectric::new(1.5).with_priority(2));
+    let wine = Arc::new(Dielectric::from_transmission(1.33, Color::new(0.8, 0.1, 0.2), 1.0).with_priority(1));
+    let center = Vec3::new(-1.3, 1.0, 0.0);
+    world.add(Arc::new(Sphere::new(center, 1.0, glass.clone())));
+    world.add(Arc::new(Sphere::new(center, -0.9, glass)));
+    world.add(Arc::new(Sphere::new(center, 0.93, wine)));
+
+    // Ice floating in water: the ice has the higher priority, so the water surface inside it
+    // is ignored.
+    let water = Arc::new(Dielectric::from_transmission(1.33, Color::new(0.7, 0.9, 0.95), 1.0).with_priority(1));
+    let ice = Arc::new(Dielectric::new(1.31).with_priority(2));
+    world.add(Arc::new(Sphere::new(Vec3::new(1.3, 1.0, 0.0), 1.0, water)));
+    world.add(Arc::new(Sphere::new(Vec3::new(1.4, 1.7, 0.3), 0.45, ice)));
+
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 16.0 / 9.0;
+    cam.image_width = 400;
+    cam.samples_per_pixel = 200;
+    cam.max_depth = 50;
+    cam.background = Color::new(0.70, 0.80, 1.00);
+
+
+    cam.vfov = 30.0;
+    cam.lookfrom = Vec3::new(0.0, 2.0, 10.0);
+    cam.lookat = Vec3::new(0.0, 1.0, 0.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+}
+
+fn dispersion() {
+    let mut world = HittableList::default();
+
+    let white = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    world.add(Arc::new(Quad::new(
+        Vec3::new(-10.0, 0.0, -10.0),
+        Vec3::new(20.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 20.0),
+        white
+    )));
+
+    // Dense flint glass (Schott SF11) disperses strongly, so the caustic under the sphere and the
+    // edges of the slab break up into colors.
+    let flint: Arc<dyn Material> = Arc::new(Dielectric::from_ior(Ior::sellmeier(
+        [1.73759695, 0.313747346, 1.89878101],
+        [0.013188707, 0.0623068142, 155.23629],
+    )));
+    world.add(Arc::new(Sphere::new(Vec3::new(-1.2, 1.0, 0.0), 1.0, flint.clone())));
+
+    let mut slab: Arc<dyn Hittable> = _box(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.5, 1.5, 0.4), flint);
+    slab = Arc::new(RotateY::new(slab, -30.0));
+    slab = Arc::new(Translate::new(slab, Vec3::new(0.8, 0.0, 0.0)));
+    world.add(slab);
+
+    // A small, bright light gives sharp caustics.
+    let light = Arc::new(DiffuseLight::from_color(Color::new(60.0, 60.0, 60.0)));
+    world.add(Arc::new(Quad::new(
+        Vec3::new(-1.5, 6.0, -0.5),
+        Vec3::new(1.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 1.0),
+        light
+    )));
+
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 16.0 / 9.0;
+    cam.image_width = 400;
+    cam.samples_per_pixel = 500;
+    cam.max_depth = 50;
+    cam.background = Color::new(0.02, 0.02, 0.02);
+    cam.spectral = true;
+
+
+    cam.vfov = 30.0;
+    cam.lookfrom = Vec3::new(0.0, 4.0, 9.0);
+    cam.lookat = Vec3::new(0.0, 0.8, 0.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+}
+
+fn principled_spheres() {
+    let mut world = HittableList::default();
+
+    let checker = Arc::new(Checkered::from_colors(0.32, Color::new(0.2, 0.3, 0.1), Color::new(0.9, 0.9, 0.9)));
+    world.add(Arc::new(Sphere::new(
+        Vec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(checker))
+    )));
+
+    let value = |v: f64| -> Arc<SolidColor> {Arc::new(SolidColor::from_value(v))};
+
+    // Glossy plastic.
+    let plastic = Principled::from_color(Color::new(0.8, 0.1, 0.1));
+
+    // Brushed gold.
+    let mut gold = Principled::from_color(Color::new(1.0, 0.78, 0.34));
+    gold.metallic = value(1.0);
+    gold.roughness = value(0.3);
+
+    // Rough paint under a glossy clearcoat.
+    let mut car_paint = Principled::from_color(Color::new(0.05, 0.2, 0.6));
+    car_paint.roughness = value(0.7);
+    car_paint.clearcoat = value(1.0);
+
+    // Velvet.
+    let mut velvet = Principled::from_color(Color::new(0.3, 0.05, 0.3));
+    velvet.roughness = value(1.0);
+    velvet.sheen = value(1.0);
+
+    // Smoky glass.
+    let mut glass = Principled::from_color(Color::new(0.9, 0.9, 0.9));
+    glass.roughness = value(0.1);
+    glass.transmission = value(1.0);
+
+    // Metal where the noise is bright, rough plastic elsewhere, and a faint glow.
+    let mut patchy = Principled::from_color(Color::new(0.7, 0.7, 0.7));
+    patchy.metallic = Arc::new(NoiseTexture::new(3.0));
+    patchy.roughness = value(0.4);
+    patchy.emission = Arc::new(SolidColor::new(Color::new(0.05, 0.03, 0.0)));
+
+    let materials: [Arc<dyn Material>; 6] = [
+        Arc::new(plastic), Arc::new(gold), Arc::new(car_paint),
+        Arc::new(velvet), Arc::new(glass), Arc::new(patchy),
+    ];
+    for (i, mat) in materials.into_iter().enumerate() {
+        let x = -5.0 + 2.0 * i as f64;
+        world.add(Arc::new(Sphere::new(Vec3::new(x, 0.9, 0.0), 0.9, mat)));
+    }
+
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 16.0 / 9.0;
+    cam.image_width = 400;
+    cam.samples_per_pixel = 200;
+    cam.max_depth = 50;
+    cam.background = Color::new(0.70, 0.80, 1.00);
+
+
+    cam.vfov = 35.0;
+    cam.lookfrom = Vec3::new(0.0, 3.0, 14.0);
+    cam.lookat = Vec3::new(0.0, 0.8, 0.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+}
+
+fn coated_spheres() {
+    let mut world = HittableList::default();
+
+    let checker = Arc::new(Checkered::from_colors(0.32, Color::new(0.2, 0.3, 0.1), Color::new(0.9, 0.9, 0.9)));
+    world.add(Arc::new(Sphere::new(
+        Vec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(checker))
+    )));
+
+    let clear = Color::new(1.0, 1.0, 1.0);
+
+    // Car paint: a glossy clear coat over red paint.
+    let paint = Arc::new(Lambertian::new(Color::new(0.7, 0.05, 0.05)));
+    let car_paint = Arc::new(Layered::new(paint, 1.5, 0.05, clear));
+
+    // Varnished wood: an amber, slightly rough varnish over a grainy base.
+    let grain = Arc::new(Lambertian::from_texture(Arc::new(NoiseTexture::new(6.0))));
+    let varnish = Arc::new(Layered::new(grain, 1.5, 0.15, Color::new(0.9, 0.7, 0.4)));
+
+    // Lacquered brushed metal.
+    let brushed = Arc::new(Metal::new(Color::new(0.8, 0.8, 0.85), 0.4));
+    let lacquer = Arc::new(Layered::new(brushed, 1.5, 0.0, clear));
+
+    world.add(Arc::new(Sphere::new(Vec3::new(-2.2, 1.0, 0.0), 1.0, car_paint)));
+    world.add(Arc::new(Sphere::new(Vec3::new( 0.0, 1.0, 0.0), 1.0, varnish)));
+    world.add(Arc::new(Sphere::new(Vec3::new( 2.2, 1.0, 0.0), 1.0, lacquer)));
+
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 16.0 / 9.0;
+    cam.image_width = 400;
+    cam.samples_per_pixel = 200;
+    cam.max_depth = 50;
+    cam.background = Color::new(0.70, 0.80, 1.00);
+
+
+    cam.vfov = 30.0;
+    cam.lookfrom = Vec3::new(0.0, 2.0, 10.0);
+    cam.lookat = Vec3::new(0.0, 1.0, 0.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+}
+
+fn mixed_materials() {
+    let mut world = HittableList::default();
+
+    let ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    world.add(Arc::new(Sphere::new(Vec3::new(0.0, -1000.0, 0.0), 1000.0, ground)));
+
+    // Rust patches on polished steel.
+    let steel = Arc::new(Metal::new(Color::new(0.8, 0.8, 0.85), 0.05));
+    let rust = Arc::new(Lambertian::new(Color::new(0.45, 0.18, 0.06)));
+    let patches = Arc::new(NoiseTexture::new(3.0));
+    world.add(Arc::new(Sphere::new(Vec3::new(-2.2, 1.0, 0.0), 1.0, Arc::new(MixMaterial::new(steel, rust, patches)))));
+
+    // Gold and matte checks.
+    let mut gold = Principled::from_color(Color::new(1.0, 0.78, 0.34));
+    gold.metallic = Arc::new(SolidColor::from_value(1.0));
+    gold.roughness = Arc::new(SolidColor::from_value(0.25));
+    let matte = Arc::new(Lambertian::new(Color::new(0.1, 0.1, 0.1)));
+    let checks = Arc::new(Checkered::from_colors(4.0, Color::new(0.0, 0.0, 0.0), Color::new(1.0, 1.0, 1.0)));
+    world.add(Arc::new(Sphere::new(Vec3::new(0.0, 1.0, 0.0), 1.0, Arc::new(MixMaterial::new(Arc::new(gold), matte, checks)))));
+
+    // Glossy oceans and matte land on the globe.
+    let land = Arc::new(Lambertian::new(Color::new(0.35, 0.3, 0.2)));
+    let water = Arc::new(Metal::new(Color::new(0.2, 0.35, 0.6), 0.1));
+    let map = Arc::new(ImageTexture::new("earthmap.jpg"));
+    world.add(Arc::new(Sphere::new(Vec3::new(2.2, 1.0, 0.0), 1.0, Arc::new(MixMaterial::new(water, land, map)))));
+
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 16.0 / 9.0;
+    cam.image_width = 400;
+    cam.samples_per_pixel = 200;
+    cam.max_depth = 50;
+    cam.background = Color::new(0.70, 0.80, 1.00);
+
+
+    cam.vfov = 30.0;
+    cam.lookfrom = Vec3::new(0.0, 2.0, 10.0);
+    cam.lookat = Vec3::new(0.0, 1.0, 0.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+}
+
+fn subsurface_spheres() {
+    let mut world = HittableList::default();
+
+    let ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    world.add(Arc::new(Sphere::new(Vec3::new(0.0, -1000.0, 0.0), 1000.0, ground)));
+
+    // Marble scatters tightly, wax and skin let red light travel much further than blue.
+    let marble = Arc::new(Subsurface::new(Color::new(0.83, 0.79, 0.75), Color::new(0.1, 0.1, 0.1), 1.5));
+    let wax = Arc::new(Subsurface::new(Color::new(0.9, 0.7, 0.4), Color::new(0.5, 0.3, 0.15), 1.45));
+    let skin = Arc::new(Subsurface::new(Color::new(0.8, 0.5, 0.4), Color::new(0.4, 0.15, 0.08), 1.4));
+
+    world.add(Arc::new(Sphere::new(Vec3::new(-2.2, 1.0, 0.0), 1.0, marble)));
+    world.add(Arc::new(Sphere::new(Vec3::new( 0.0, 1.0, 0.0), 1.0, wax)));
+    world.add(Arc::new(Sphere::new(Vec3::new( 2.2, 1.0, 0.0), 1.0, skin)));
+
+    // Light from behind shows how far light bleeds through the objects.
+    let light = Arc::new(DiffuseLight::from_color(Color::new(8.0, 8.0, 8.0)));
+    world.add(Arc::new(Quad::new(
+        Vec3::new(-4.0, 0.5, -4.0),
+        Vec3::new(8.0, 0.0, 0.0),
+        Vec3::new(0.0, 4.0, 0.0),
+        light
+    )));
+
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 16.0 / 9.0;
+    cam.image_width = 400;
+    cam.samples_per_pixel = 200;
+    cam.max_depth = 200;
+    cam.background = Color::new(0.1, 0.1, 0.12);
+
+
+    cam.vfov = 30.0;
+    cam.lookfrom = Vec3::new(0.0, 2.0, 10.0);
+    cam.lookat = Vec3::new(0.0, 1.0, 0.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+}
+
+fn thin_films() {
+    let mut world = HittableList::default();
+
+    let checker = Arc::new(Checkered::from_colors(0.32, Color::new(0.2, 0.3, 0.1), Color::new(0.9, 0.9, 0.9)));
+    world.add(Arc::new(Sphere::new(
+        Vec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(checker))
+    )));
+
+    // A soap bubble: a film of soapy water around air, swirling up to 900 nm thick.
+    let swirl = Arc::new(NoiseTexture::new(1.5));
+    let soap = ThinFilm::from_texture(1.33, swirl, 900.0);
+    let bubble = Arc::new(Dielectric::new(1.0).with_thin_film(soap));
+    world.add(Arc::new(Sphere::new(Vec3::new(-2.2, 1.2, 0.0), 1.0, bubble)));
+
+    // Anodized titanium: an oxide layer on a gray metal.
+    let oxide = ThinFilm::new(2.4, 250.0);
+    let titanium = Arc::new(Metal::new(Color::new(0.55, 0.5, 0.45), 0.05).with_thin_film(oxide));
+    world.add(Arc::new(Sphere::new(Vec3::new(0.0, 1.0, 0.0), 1.0, titanium)));
+
+    // A lens with a magnesium fluoride anti-reflection coating.
+    let coating = ThinFilm::new(1.38, 100.0);
+    let lens = Arc::new(Dielectric::new(1.5).with_thin_film(coating));
+    world.add(Arc::new(Sphere::new(Vec3::new(2.2, 1.0, 0.0), 1.0, lens)));
+
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 16.0 / 9.0;
+    cam.image_width = 400;
+    cam.samples_per_pixel = 200;
+    cam.max_depth = 50;
+    cam.background = Color::new(0.70, 0.80, 1.00);
+
+
+    cam.vfov = 30.0;
+    cam.lookfrom = Vec3::new(0.0, 2.0, 10.0);
+    cam.lookat = Vec3::new(0.0, 1.0, 0.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+}
+
+fn cornell_fog() {
+    let mut world = HittableList::default();
+
+    let red = Arc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05)));
+    let white = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    let green = Arc::new(Lambertian::new(Color::new(0.12, 0.45, 0.15)));
+    let light = Arc::new(DiffuseLight::from_color(Color::new(7.0, 7.0, 7.0)));
+
+    world.add(
+        Arc::new(Quad::new(
+            Vec3::new(555.0, 0.0, 0.0), 
+            Vec3::new(0.0, 555.0, 0.0), 
+            Vec3::new(0.0, 0.0, 555.0), 
+        green)
+        )
+    );
+    world.add(
+        Arc::new(Quad::new(
+            Vec3::new(0.0, 0.0, 0.0), 
+            Vec3::new(0.0, 555.0, 0.0), 
+            Vec3::new(0.0, 0.0, 555.0), 
+        red)
+        )
+    );
+    world.add(
+        Arc::new(Quad::new(
+            Vec3::new(113.0, 554.0, 127.0), 
+            Vec3::new(330.0, 0.0, 0.0), 
+            Vec3::new(0.0, 0.0, 305.0), 
+        light)
+        )
+    );
+    world.add(
+        Arc::new(Quad::new(
+            Vec3::new(0.0, 0.0, 0.0), 
+            Vec3::new(555.0, 0.0, 0.0), 
+            Vec3::new(0.0, 0.0, 555.0), 
+        white.clone())
+        )
+    );
+    world.add(
+        Arc::new(Quad::new(
+            Vec3::new(555.0, 555.0, 555.0), 
+            Vec3::new(-555.0, 0.0, 0.0), 
+            Vec3::new(0.0, 0.0, -555.0), 
+        white.clone())
+        )
+    );
+    world.add(
+        Arc::new(Quad::new(
+            Vec3::new(0.0, 0.0, 555.0), 
+            Vec3::new(555.0, 0.0, 0.0), 
+            Vec3::new(0.0, 555.0, 0.0), 
+        white.clone())
+        )
+    );
+
+    let mut box1:Arc<dyn Hittable> = _box(
+        Vec3::new(0.0,0.0,0.0),
+        Vec3::new(165.0, 330.0, 165.0),
+        white.clone()
+    );
+
+    box1 = Arc::new(RotateY::new(box1, 15.0));
+    box1 = Arc::new(Translate::new(box1, Vec3::new(265.0, 0.0, 295.0)));
+    
+
+    let mut box2: Arc<dyn Hittable> = _box(
+        Vec3::new(0.0,0.0,0.0),
+        Vec3::new(165.0, 165.0, 165.0),
+        white.clone()
+    );
+    box2 = Arc::new(RotateY::new(box2, -18.0));
+    box2 = Arc::new(Translate::new(box2, Vec3::new(130.0, 0.0, 65.0)));
+    
+    // Fog scatters strongly forward; the cloud adds a weak backward lobe for its silver lining.
+    let fog = Arc::new(HenyeyGreenstein::from_color(Color::new(0.9, 0.9, 0.9), 0.8));
+    let cloud = Arc::new(HenyeyGreenstein::two_lobe(
+        Arc::new(SolidColor::new(Color::new(1.0, 1.0, 1.0))),
+        0.7, -0.3, 0.2
+    ));
+    world.add(Arc::new(ConstantMedium::with_phase_function(box1, 0.01, fog)));
+    world.add(Arc::new(ConstantMedium::with_phase_function(box2, 0.01, cloud)));
+    
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 1.0;
+    cam.image_width = 600;
+    cam.samples_per_pixel = 200;
+    cam.max_depth = 50;
+    cam.background = Color::new(0.0, 0.0, 0.0);
+
+
+    cam.vfov = 40.0;
+    cam.lookfrom = Vec3::new(278.0, 278.0, -800.0);
+    cam.lookat = Vec3::new(278.0, 278.0, 0.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+
+}
+fn heterogeneous_volumes() {
+    let mut world = HittableList::default();
+
+    world.add(
+        Arc::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))))
+        )
+    );
+
+    // A cloud whose density follows Perlin turbulence.
+    let cloud_boundary = Arc::new(Sphere::new(
+        Vec3::new(0.0, 2.0, -2.2),
+        2.0,
+        Arc::new(Dielectric::new(1.5)))
+    );
+    let cloud = Arc::new(HenyeyGreenstein::from_color(Color::new(1.0, 1.0, 1.0), 0.6));
+    world.add(Arc::new(HeterogeneousMedium::with_phase_function(
+        cloud_boundary, Arc::new(NoiseTexture::new(2.0)), 1.2, cloud
+    )));
+
+    // A smoke plume stored in a voxel grid: a column that widens and thins out as it rises.
+    let bounds = Aabb::from_points(Vec3::new(-1.0, 0.0, 1.2), Vec3::new(1.0, 4.0, 3.2));
+    let plume = Arc::new(VoxelGrid::from_fn(32, 64, 32, bounds, |p| {
+        let sway = 0.15 * (p.y * 9.0).sin();
+        let radius = 0.08 + 0.3 * p.y;
+        let r2 = (p.x - 0.5 - sway).powi(2) + (p.z - 0.5).powi(2);
+        (-r2 / (radius * radius)).exp() * (1.0 - p.y)
+    }));
+    let plume_boundary = _box(
+        Vec3::new(-1.0, 0.0, 1.2),
+        Vec3::new(1.0, 4.0, 3.2),
+        Arc::new(Lambertian::new(Color::new(0.0, 0.0, 0.0)))
+    );
+    world.add(Arc::new(HeterogeneousMedium::from_color(
+        plume_boundary, plume, 6.0, Color::new(0.6, 0.6, 0.6)
+    )));
+
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 16.0 / 9.0;
+    cam.image_width = 400;
+    cam.samples_per_pixel = 100;
+    cam.max_depth = 50;
+    cam.background = Color::new(0.70, 0.80, 1.00);
+
+
+    cam.vfov = 30.0;
+    cam.lookfrom = Vec3::new(13.0, 2.0, 3.0);
+    cam.lookat = Vec3::new(0.0, 1.8, 0.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+}
+
+fn smoke_ring() {
+    let mut world = HittableList::default();
+
+    world.add(
+        Arc::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))))
+        )
+    );
+
+    // A density field exported as a sparse voxel file, tilted and lifted off the ground.
+    let bounds = Aabb::from_points(Vec3::new(-2.0, -2.0, -2.0), Vec3::new(2.0, 2.0, 2.0));
+    let grid = Arc::new(VoxelGrid::from_file("smoke_ring.voxels", bounds));
+    let mut ring: Arc<dyn Hittable> = Arc::new(
+        HeterogeneousMedium::from_voxel_grid(grid, 8.0, Color::new(0.8, 0.8, 0.8))
+    );
+    ring = Arc::new(RotateY::new(ring, 30.0));
+    ring = Arc::new(Translate::new(ring, Vec3::new(0.0, 1.5, 0.0)));
+    world.add(ring);
+
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 16.0 / 9.0;
+    cam.image_width = 400;
+    cam.samples_per_pixel = 100;
+    cam.max_depth = 50;
+    cam.background = Color::new(0.70, 0.80, 1.00);
+
+
+    cam.vfov = 30.0;
+    cam.lookfrom = Vec3::new(8.0, 4.0, 2.0);
+    cam.lookat = Vec3::new(0.0, 1.4, 0.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+}
+
+fn glowing_volumes() {
+    let mut world = HittableList::default();
+
+    world.add(
+        Arc::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))))
+        )
+    );
+
+    // A flame whose hot core glows white-yellow and whose thin edges glow a dull red.
+    let bounds = Aabb::from_points(Vec3::new(-1.0, 0.0, 1.0), Vec3::new(1.0, 3.5, 3.0));
+    let flame = Arc::new(VoxelGrid::from_fn(32, 56, 32, bounds, |p| {
+        let sway = 0.08 * (p.y * 12.0).sin() * p.y;
+        let radius = 0.25 * (1.0 - p.y).powf(0.7) + 0.02;
+        let r2 = (p.x - 0.5 - sway).powi(2) + (p.z - 0.5).powi(2);
+        (-r2 / (radius * radius)).exp() * (1.0 - p.y * p.y)
+    }));
+    let heat = Arc::new(Blackbody::new(flame.clone(), 1000.0, 2400.0, 6.0));
+    world.add(Arc::new(
+        HeterogeneousMedium::from_voxel_grid(flame, 5.0, Color::new(0.2, 0.2, 0.2)).with_emission(heat)
+    ));
+
+    // A ball of glowing gas.
+    let plasma = Arc::new(Sphere::new(Vec3::new(0.0, 1.5, -2.0), 1.2, Arc::new(Dielectric::new(1.0))));
+    world.add(Arc::new(
+        ConstantMedium::from_color(plasma, 0.5, Color::new(0.5, 0.5, 0.5))
+            .with_emission(Arc::new(SolidColor::new(Color::new(0.1, 0.25, 1.0))))
+    ));
+
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 16.0 / 9.0;
+    cam.image_width = 400;
+    cam.samples_per_pixel = 200;
+    cam.max_depth = 50;
+    cam.background = Color::new(0.0, 0.0, 0.0);
+
+
+    cam.vfov = 30.0;
+    cam.lookfrom = Vec3::new(13.0, 2.0, 3.0);
+    cam.lookat = Vec3::new(0.0, 1.5, 0.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+}
+
+fn blackbody_lights() {
+    let mut world = HittableList::default();
+
+    let pertext = Arc::new(NoiseTexture::new(4.0));
+    world.add(
+        Arc::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            Arc::new(Lambertian::from_texture(pertext.clone())))
+        )
+    );
+    world.add(
+        Arc::new(Sphere::new(
+            Vec3::new(0.0, 2.0, 0.0),
+            2.0,
+            Arc::new(Lambertian::from_texture(pertext)))
+        )
+    );
+
+    // A cool daylight bulb overhead and a warm incandescent panel to the side. Their output
+    // does not depend on their size, only on the power they are given.
+    world.add(Arc::new(DiffuseLight::blackbody(6500.0, Power::Watts(3000.0), |light| {
+        Sphere::new(Vec3::new(0.0, 7.0, 0.0), 1.0, light)
+    })));
+
+    // Lights given by their power shine from the front; the panel's faces +z, towards the
+    // spheres.
+    world.add(Arc::new(DiffuseLight::blackbody(2700.0, Power::Lumens(35000.0), |light| {
+        Quad::new(Vec3::new(3.0, 1.0, -2.0), Vec3::new(2.0, 0.0, 0.0), Vec3::new(0.0, 2.0, 0.0), light)
+    })));
+
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 16.0 / 9.0;
+    cam.image_width = 400;
+    cam.samples_per_pixel = 100;
+    cam.max_depth = 50;
+    cam.background = Color::new(0.0, 0.0, 0.0);
+
+
+    cam.vfov = 20.0;
+    cam.lookfrom = Vec3::new(26.0, 3.0, 6.0);
+    cam.lookat = Vec3::new(0.0, 2.0, 0.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+}
+
+fn ies_lights() {
+    let mut world = HittableList::default();
+
+    let white = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    world.add(
+        Arc::new(Quad::new(
+            Vec3::new(-6.0, 0.0, -3.0),
+            Vec3::new(12.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 8.0),
+            white.clone()
+        ))
+    );
+    world.add(
+        Arc::new(Quad::new(
+            Vec3::new(-6.0, 0.0, -3.0),
+            Vec3::new(12.0, 0.0, 0.0),
+            Vec3::new(0.0, 5.0, 0.0),
+            white
+        ))
+    );
+
+    // Recessed downlights close to the wall throw the familiar scallops onto it. They face
+    // down, so they only emit from their front.
+    let downlight = Arc::new(IesProfile::new("downlight.ies"));
+    let mut lights = HittableList::default();
+    for x in [-3.5, 0.0, 3.5] {
+        let light = DiffuseLight::from_color(Color::new(60.0, 50.0, 38.0))
+            .with_sides(EmittingSides::Front)
+            .with_profile(downlight.clone());
+        let panel = Arc::new(Quad::new(
+            Vec3::new(x - 0.2, 4.5, -2.6),
+            Vec3::new(0.4, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 0.4),
+            Arc::new(light)
+        ));
+        world.add(panel.clone());
+        lights.add(panel);
+    }
+
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 16.0 / 9.0;
+    cam.image_width = 400;
+    cam.samples_per_pixel = 100;
+    cam.max_depth = 50;
+    cam.background = Color::new(0.0, 0.0, 0.0);
+
+
+    cam.vfov = 50.0;
+    cam.lookfrom = Vec3::new(0.0, 2.0, 6.0);
+    cam.lookat = Vec3::new(0.0, 2.2, -3.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.lights = Some(Arc::new(lights));
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+}
+
+fn emissive_panels() {
+    let mut world = HittableList::default();
+    let mut lights = HittableList::default();
+
+    let white = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    world.add(
+        Arc::new(Quad::new(
+            Vec3::new(-6.0, 0.0, -3.0),
+            Vec3::new(12.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 8.0),
+            white.clone()
+        ))
+    );
+
+    // A screen showing the earth. Light sampling favors its bright continents and clouds
+    // over the dark oceans.
+    let screen_texture = Arc::new(ImageTexture::new("earthmap.jpg"));
+    let screen = Arc::new(Quad::new(
+        Vec3::new(-3.2, 0.6, -3.0),
+        Vec3::new(6.4, 0.0, 0.0),
+        Vec3::new(0.0, 3.2, 0.0),
+        Arc::new(DiffuseLight::new(screen_texture).with_intensity(4.0).with_sides(EmittingSides::Front))
+    ));
+    world.add(screen.clone());
+    lights.add(screen);
+
+    world.add(Arc::new(Sphere::new(Vec3::new(-1.5, 0.7, 0.0), 0.7, white)));
+    world.add(Arc::new(Sphere::new(Vec3::new(0.3, 0.5, 0.8), 0.5, Arc::new(Principled::from_color(Color::new(0.8, 0.3, 0.2))))));
+    world.add(Arc::new(Sphere::new(Vec3::new(1.8, 0.6, 0.2), 0.6, Arc::new(Metal::new(Color::new(0.9, 0.9, 0.9), 0.05)))));
+
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 16.0 / 9.0;
+    cam.image_width = 400;
+    cam.samples_per_pixel = 100;
+    cam.max_depth = 50;
+    cam.background = Color::new(0.0, 0.0, 0.0);
+
+
+    cam.vfov = 45.0;
+    cam.lookfrom = Vec3::new(0.0, 2.0, 6.0);
+    cam.lookat = Vec3::new(0.0, 1.4, -3.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.lights = Some(Arc::new(lights));
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+}
+
+fn texture_filtering() {
+    let mut world = HittableList::default();
+
+    // The earth tiled across the ground, mirrored at every seam and turned a little. The
+    // MIP-mapped filter keeps the far tiles from shimmering.
+    let tiles = ImageTexture::new("earthmap.jpg")
+        .with_filter(Filter::Trilinear)
+        .with_wrap(Wrap::Mirror)
+        .with_transform(UvTransform::new((3.0, 3.0), (0.0, 0.0), 20.0));
+    world.add(
+        Arc::new(Quad::new(
+            Vec3::new(-8.0, 0.0, -6.0),
+            Vec3::new(16.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 12.0),
+            Arc::new(Lambertian::from_texture(Arc::new(tiles)))
+        ))
+    );
+
+    // The same few dozen texels of the map, magnified with each filter.
+    let closeup = UvTransform::new((0.015, 0.03), (0.525, 0.71), 0.0);
+    let filters = [Filter::Nearest, Filter::Bilinear, Filter::Bicubic];
+    for (i, filter) in filters.into_iter().enumerate() {
+        let texture = ImageTexture::new("earthmap.jpg").with_filter(filter).with_transform(closeup);
+        world.add(
+            Arc::new(Quad::new(
+                Vec3::new(-3.3 + 2.2 * i as f64, 0.5, -1.0),
+                Vec3::new(2.0, 0.0, 0.0),
+                Vec3::new(0.0, 2.0, 0.0),
+                Arc::new(Lambertian::from_texture(Arc::new(texture)))
+            ))
+        );
+    }
+
+    // A decal: the map shrunk to the middle of the panel, with a border color around it.
+    let decal = ImageTexture::new("earthmap.jpg")
+        .with_filter(Filter::Bilinear)
+        .with_wrap(Wrap::Border(Color::new(0.8, 0.1, 0.1)))
+        .with_transform(UvTransform::new((1.6, 1.6), (-0.3, -0.3), 0.0));
+    world.add(
+        Arc::new(Quad::new(
+            Vec3::new(-1.5, 2.7, -1.0),
+            Vec3::new(3.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.5, 0.0),
+            Arc::new(Lambertian::from_texture(Arc::new(decal)))
+        ))
+    );
+
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 16.0 / 9.0;
+    cam.image_width = 400;
+    cam.samples_per_pixel = 50;
+    cam.max_depth = 50;
+    cam.background = Color::new(0.70, 0.80, 1.00);
+
+
+    cam.vfov = 50.0;
+    cam.lookfrom = Vec3::new(0.0, 3.0, 6.0);
+    cam.lookat = Vec3::new(0.0, 1.8, -1.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+}
+
+fn data_textures() {
+    let mut world = HittableList::default();
+
+    let checker = Arc::new(Checkered::from_colors(0.32, Color::new(0.2, 0.3, 0.1), Color::new(0.9, 0.9, 0.9)));
+    world.add(Arc::new(Sphere::new(
+        Vec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(checker))
+    )));
+
+    // Color maps are sRGB encoded, but a roughness map stores plain numbers. It is a 16 bit
+    // file, so the smooth bands do not step.
+    let roughness = Arc::new(ImageTexture::with_color_space("roughness_bands.png", ColorSpace::Linear).with_filter(Filter::Bilinear));
+
+    let mut earth = Principled::new(Arc::new(ImageTexture::new("earthmap.jpg").with_filter(Filter::Bilinear)));
+    earth.roughness = roughness.clone();
+    world.add(Arc::new(Sphere::new(Vec3::new(-1.1, 1.0, 0.0), 1.0, Arc::new(earth))));
+
+    let mut steel = Principled::from_color(Color::new(0.8, 0.8, 0.85));
+    steel.metallic = Arc::new(SolidColor::from_value(1.0));
+    steel.roughness = roughness;
+    world.add(Arc::new(Sphere::new(Vec3::new(1.1, 1.0, 0.0), 1.0, Arc::new(steel))));
+
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 16.0 / 9.0;
+    cam.image_width = 400;
+    cam.samples_per_pixel = 100;
+    cam.max_depth = 50;
+    cam.background = Color::new(0.70, 0.80, 1.00);
+
+
+    cam.vfov = 30.0;
+    cam.lookfrom = Vec3::new(0.0, 2.0, 8.0);
+    cam.lookat = Vec3::new(0.0, 0.9, 0.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+}
+
+fn bump_mapping() {
+    let mut world = HittableList::default();
+
+    // Beveled tiles from a normal map, on a plain quad.
+    let tiles = Arc::new(ImageTexture::with_color_space("tiles_normal.png", ColorSpace::Linear)
+        .with_filter(Filter::Bilinear)
+        .with_wrap(Wrap::Repeat)
+        .with_transform(UvTransform::new((3.0, 3.0), (0.0, 0.0), 0.0)));
+    let floor = Arc::new(NormalMap::new(Arc::new(Lambertian::new(Color::new(0.6, 0.55, 0.5))), tiles.clone()));
+    world.add(Arc::new(Quad::new(Vec3::new(-6.0, 0.0, 6.0), Vec3::new(12.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -12.0), floor)));
+
+    // A noise texture as a height field roughens a sphere without adding any geometry.
+    let stone = Arc::new(NormalMap::bump(
+        Arc::new(Lambertian::new(Color::new(0.7, 0.7, 0.7))),
+        Arc::new(NoiseTexture::new(4.0)),
+        0.02,
+    ));
+    world.add(Arc::new(Sphere::new(Vec3::new(-1.2, 1.0, 0.0), 1.0, stone)));
+
+    // The same tiles on a glossy triangle, mapped through per-vertex texture coordinates.
+    let mut glaze = Principled::from_color(Color::new(0.2, 0.35, 0.6));
+    glaze.roughness = Arc::new(SolidColor::from_value(0.2));
+    world.add(Arc::new(Triangle::with_uvs(
+        Vec3::new(0.4, 0.0, -0.5),
+        Vec3::new(2.6, 0.0, -0.5),
+        Vec3::new(1.5, 2.2, -0.8),
+        [(0.0, 0.0), (1.0, 0.0), (0.5, 1.0)],
+        Arc::new(NormalMap::new(Arc::new(glaze), tiles)),
+    )));
+
+    // A low light rakes across the surfaces so the relief casts visible shading.
+    let light = Arc::new(Quad::new(
+        Vec3::new(-5.0, 1.5, 2.0),
+        Vec3::new(0.0, 0.0, -2.0),
+        Vec3::new(0.0, 2.0, 0.0),
+        Arc::new(DiffuseLight::from_color(Color::new(12.0, 11.0, 10.0))),
+    ));
+    world.add(light.clone());
+
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 16.0 / 9.0;
+    cam.image_width = 400;
+    cam.samples_per_pixel = 100;
+    cam.max_depth = 50;
+    cam.background = Color::new(0.10, 0.12, 0.16);
+    cam.lights = Some(light);
+
+    cam.vfov = 35.0;
+    cam.lookfrom = Vec3::new(0.0, 3.0, 7.0);
+    cam.lookat = Vec3::new(0.0, 0.8, 0.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+}
+
+fn cutouts() {
+    let mut world = HittableList::default();
+
+    let checker = Arc::new(Checkered::from_colors(0.32, Color::new(0.2, 0.3, 0.1), Color::new(0.9, 0.9, 0.9)));
+    world.add(Arc::new(Sphere::new(
+        Vec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(checker))
+    )));
+
+    // A chain link fence: one quad, with the gaps between the wires cut out by an image mask.
+    let wires = Arc::new(ImageTexture::with_color_space("chain_link.png", ColorSpace::Linear)
+        .with_wrap(Wrap::Repeat)
+        .with_transform(UvTransform::new((6.0, 3.0), (0.0, 0.0), 0.0)));
+    let fence = Arc::new(Quad::new(
+        Vec3::new(-3.0, 0.0, -0.5),
+        Vec3::new(6.0, 0.0, 0.0),
+        Vec3::new(0.0, 3.0, 0.0),
+        Arc::new(Metal::new(Color::new(0.7, 0.7, 0.72), 0.3)),
+    ));
+    world.add(Arc::new(AlphaMask::new(fence, wires)));
+
+    // Behind it, a ball whose shell fades in and out with noise, blended stochastically.
+    let shell = Arc::new(Sphere::new(Vec3::new(0.0, 1.0, -2.5), 1.0, Arc::new(Lambertian::new(Color::new(0.8, 0.3, 0.2)))));
+    world.add(Arc::new(AlphaMask::new(shell, Arc::new(NoiseTexture::new(3.0))).stochastic()));
+
+    // The sun shines through the fence, so its shadow shows the same holes.
+    let light = Arc::new(Quad::new(
+        Vec3::new(-2.0, 6.0, -7.0),
+        Vec3::new(4.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 4.0),
+        Arc::new(DiffuseLight::from_color(Color::new(8.0, 7.5, 6.5))),
+    ));
+    world.add(light.clone());
+
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 16.0 / 9.0;
+    cam.image_width = 400;
+    cam.samples_per_pixel = 100;
+    cam.max_depth = 50;
+    cam.background = Color::new(0.35, 0.40, 0.50);
+    cam.lights = Some(light);
+
+    cam.vfov = 40.0;
+    cam.lookfrom = Vec3::new(2.0, 2.5, 6.0);
+    cam.lookat = Vec3::new(0.0, 1.0, -1.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+}
+
+fn texture_mapping() {
+    let mut world = HittableList::default();
+
+    world.add(Arc::new(Quad::new(
+        Vec3::new(-20.0, 0.0, 20.0), Vec3::new(40.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -40.0),
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    )));
+
+    // Checks in texture coordinates wrap around the sphere like the earth map does, narrowing
+    // towards the poles.
+    let globe = Arc::new(UvCheckered::from_colors((16.0, 8.0), Color::new(0.8, 0.2, 0.1), Color::new(0.9, 0.9, 0.9)));
+    world.add(Arc::new(Sphere::new(Vec3::new(-3.2, 1.0, 0.0), 1.0, Arc::new(Lambertian::from_texture(globe)))));
+
+    // And they stay square on a turned panel, here also rotated within the surface by a UV
+    // transform.
+    let tilted = Arc::new(MappedTexture::new(
+        Arc::new(UvCheckered::from_colors((4.0, 4.0), Color::new(0.1, 0.2, 0.6), Color::new(0.9, 0.9, 0.9))),
+        Mapping::Uv(UvTransform::new((1.0, 1.0), (0.0, 0.0), 45.0)),
+    ));
+    let panel = Arc::new(Quad::new(
+        Vec3::new(-0.9, 0.0, 0.0), Vec3::new(1.8, 0.0, 0.0), Vec3::new(0.0, 1.8, 0.0),
+        Arc::new(Lambertian::from_texture(tilted)),
+    ));
+    world.add(Arc::new(Translate::new(Arc::new(RotateY::new(panel, 30.0)), Vec3::new(-1.0, 0.0, 0.0))));
+
+    // A solid checker read in object space turns together with the box, where a world space
+    // one would cut across its edges.
+    let blocks = Arc::new(MappedTexture::new(
+        Arc::new(Checkered::from_colors(2.5, Color::new(0.2, 0.3, 0.1), Color::new(0.9, 0.9, 0.9))),
+        Mapping::Object,
+    ));
+    let cube = _box(Vec3::new(-0.6, 0.2, -0.6), Vec3::new(0.6, 1.4, 0.6), Arc::new(Lambertian::from_texture(blocks)));
+    world.add(Arc::new(Translate::new(Arc::new(RotateY::new(cube, 25.0)), Vec3::new(1.3, -0.2, 0.0))));
+
+    // Triplanar projection covers a sphere without texture coordinates or a seam.
+    let projected = Arc::new(MappedTexture::new(
+        Arc::new(ImageTexture::new("earthmap.jpg").with_filter(Filter::Bilinear).with_wrap(Wrap::Repeat)),
+        Mapping::Triplanar{scale: 0.4, sharpness: 4.0},
+    ));
+    world.add(Arc::new(Sphere::new(Vec3::new(3.6, 1.0, 0.0), 1.0, Arc::new(Lambertian::from_texture(projected)))));
+
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 16.0 / 9.0;
+    cam.image_width = 400;
+    cam.samples_per_pixel = 100;
+    cam.max_depth = 50;
+    cam.background = Color::new(0.70, 0.80, 1.00);
+
+    cam.vfov = 40.0;
+    cam.lookfrom = Vec3::new(0.0, 3.0, 9.0);
+    cam.lookat = Vec3::new(0.0, 0.9, 0.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+}
+
+fn procedural_textures() {
+    let mut world = HittableList::default();
+
+    world.add(Arc::new(Quad::new(
+        Vec3::new(-20.0, 0.0, 20.0), Vec3::new(40.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -40.0),
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    )));
+
+    let wood = Arc::new(Wood::new(8.0, 0.6, ColorRamp::new(vec![
+        (0.0, Color::new(0.75, 0.52, 0.30)),
+        (1.0, Color::new(0.35, 0.18, 0.08)),
+    ])));
+    let marble = Arc::new(Marble::new(3.0, 8.0, ColorRamp::new(vec![
+        (0.0, Color::new(0.15, 0.18, 0.22)),
+        (0.25, Color::new(0.60, 0.62, 0.62)),
+        (1.0, Color::new(0.92, 0.91, 0.88)),
+    ])));
+
+    // Top row, the scalar patterns; bottom row, colored ones. The wood sits on its ring axis,
+    // and the last is marble swirled by a domain warp.
+    let textures: [Arc<dyn Texture>; 6] = [
+        Arc::new(Fbm::new(3.0)),
+        Arc::new(Ridged::new(2.0)),
+        Arc::new(Cellular::new(4.0, CellFeature::Edges)),
+        marble.clone(),
+        wood,
+        Arc::new(DomainWarp::new(marble, 1.5, 0.6)),
+    ];
+    for (i, texture) in textures.into_iter().enumerate() {
+        let center = Vec3::new(-2.2 + 2.2 * (i % 3) as f64, 2.8 - 1.9 * (i / 3) as f64, 0.0);
+        world.add(Arc::new(Sphere::new(center, 0.8, Arc::new(Lambertian::from_texture(texture)))));
+    }
+
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 16.0 / 9.0;
+    cam.image_width = 400;
+    cam.samples_per_pixel = 100;
+    cam.max_depth = 50;
+    cam.background = Color::new(0.70, 0.80, 1.00);
+
+    cam.vfov = 28.0;
+    cam.lookfrom = Vec3::new(0.0, 2.5, 12.0);
+    cam.lookat = Vec3::new(0.0, 1.85, 0.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+}
+
+fn texture_graph() {
+    let mut world = HittableList::default();
+
+    world.add(Arc::new(Quad::new(
+        Vec3::new(-20.0, 0.0, 20.0), Vec3::new(40.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -40.0),
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    )));
+
+    // A graph from a file drives two inputs of the same material.
+    let graph = TextureGraph::new("painted_wood.graph");
+    let mut painted = Principled::new(graph.output());
+    painted.roughness = graph.node("roughness").unwrap();
+    world.add(Arc::new(Sphere::new(Vec3::new(-1.1, 1.0, 0.0), 1.0, Arc::new(painted))));
+
+    // Nodes can be wired up in code too: the earth with the hues of its southern half turned
+    // around.
+    let earth = Arc::new(ImageTexture::new("earthmap.jpg").with_filter(Filter::Trilinear));
+    let shifted = Arc::new(HsvAdjust::new(earth.clone(), 0.5, 1.0, 1.0));
+    let hemispheres = Arc::new(UvCheckered::from_colors((1.0, 2.0), Color::new(0.0, 0.0, 0.0), Color::new(1.0, 1.0, 1.0)));
+    let globe = Arc::new(Lerp::new(shifted, earth, hemispheres));
+    world.add(Arc::new(Sphere::new(Vec3::new(1.1, 1.0, 0.0), 1.0, Arc::new(Lambertian::from_texture(globe)))));
+
+    let mut cam = Camera::default();
+
+    cam.aspect_ratio = 16.0 / 9.0;
+    cam.image_width = 400;
+    cam.samples_per_pixel = 100;
+    cam.max_depth = 50;
+    cam.background = Color::new(0.70, 0.80, 1.00);
+
+    cam.vfov = 30.0;
+    cam.lookfrom = Vec3::new(0.0, 2.0, 8.0);
+    cam.lookat = Vec3::new(0.0, 0.9, 0.0);
+    cam.vup = Vec3::new(0.0, 1.0, 0.0);
+
+    cam.defocus_angle = 0.0;
+
+    cam.render(&world);
+}
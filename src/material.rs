@@ -3,10 +3,24 @@ pub mod metal;
 pub mod dielectric;
 pub mod diffuse_light;
 pub mod isotropic;
+pub mod henyey_greenstein;
+pub mod volume_emission;
+pub mod ies;
+pub mod rough_dielectric;
+pub mod fresnel;
+pub mod microfacet;
+pub mod medium_stack;
+pub mod ior;
+pub mod principled;
+pub mod layered;
+pub mod mix;
+pub mod subsurface;
+pub mod thin_film;
+pub mod normal_map;
 
-use crate::utility::vec3::Vec3;
-use crate::utility::{self, color::Color, ray::Ray}; // pass utility::self to children
+use crate::utility::{self, color::Color, distribution::Distribution2D, ray::Ray}; // pass utility::self to children
 use crate::hittable::HitRecord;
+use medium_stack::Interface;
 use std::sync::Arc;
 
 
@@ -14,9 +28,50 @@ use std::sync::Arc;
 pub trait Material: Sync + Send {
     fn scatter(&self, r_in:&Ray, rec:&HitRecord, attenuation: &mut Color, scattered:&mut Ray) -> bool;
 
-    fn emitted(&self, _u:f64, _v:f64, _p:Vec3) -> Color {
+    fn emitted(&self, _r_in:&Ray, _rec:&HitRecord) -> Color {
         Color::new(0.0, 0.0, 0.0)
     }
+
+    fn eval(&self, _r_in:&Ray, _rec:&HitRecord, _scattered:&Ray) -> Color {
+        // The BSDF times the cosine term for the given scattered direction. Materials that only
+        // scatter into discrete directions (mirrors, smooth glass) cannot be evaluated and keep
+        // the default, together with a zero scattering_pdf.
+        Color::new(0.0, 0.0, 0.0)
+    }
+
+    fn scattering_pdf(&self, _r_in:&Ray, _rec:&HitRecord, _scattered:&Ray) -> f64 {
+        // Density with which scatter() picks the given direction.
+        0.0
+    }
+
+    fn scatter_sampled(&self, r_in:&Ray, rec:&HitRecord, attenuation: &mut Color, scattered:&mut Ray) -> Option<f64> {
+        // Like scatter(), but also reports the density the direction was actually sampled with,
+        // for weighting against light sampling. Zero means a delta lobe, which light sampling
+        // can never reproduce. Materials that pick one of several lobes, some of them delta,
+        // override this, since scattering_pdf() alone cannot tell which lobe was picked.
+        if !self.scatter(r_in, rec, attenuation, scattered) {
+            return None
+        }
+        Some(self.scattering_pdf(r_in, rec, scattered))
+    }
+
+    fn emission_distribution(&self) -> Option<&Distribution2D> {
+        // For emitters with textured emission, where in (u, v) the light comes from, so light
+        // sampling can favor the bright parts.
+        None
+    }
+
+    fn is_dispersive(&self) -> bool {
+        // Whether the scattered direction depends on the wavelength, so a spectral path can only
+        // follow its hero wavelength.
+        self.interface().is_some_and(|i| i.ior.is_dispersive())
+    }
+
+    fn interface(&self) -> Option<Interface> {
+        // Materials that bound a medium (dielectrics) describe it so the camera can track which
+        // medium a path is inside of. Surfaces without an interior return None.
+        None
+    }
 }
 
 pub type Mat = Arc<dyn Material>;
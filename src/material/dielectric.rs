@@ -1,13 +1,38 @@
-use super::{Material, HitRecord, utility::{rand, vec3, ray::Ray, color::Color}};
+use super::{Material, HitRecord, ior::Ior, medium_stack::Interface, thin_film::{FilmBase, ThinFilm}, utility::{rand, vec3, ray::Ray, color::Color}};
 
+// Smallest transmission turned into an absorption coefficient.
+const MIN_TRANSMISSION: f64 = 1e-4;
 
 pub struct Dielectric {
-    ir: f64, // Index of Refraction
+    ir: Ior, // Index of Refraction
+    absorption: Color, // Beer-Lambert absorption coefficient per unit length
+    priority: i32, // Which medium wins where nested dielectrics overlap
+    film: Option<ThinFilm>, // Coating on the outside of the surface
 }
 
 impl Dielectric {
     pub fn new(index_of_refraction: f64) -> Self {
-        Dielectric { ir:index_of_refraction}
+        Dielectric { ir:Ior::Constant(index_of_refraction), absorption:Color::default(), priority:0, film:None}
+    }
+
+    pub fn from_transmission(index_of_refraction: f64, transmission: Color, distance: f64) -> Self {
+        // The glass lets through the given color after light travels the given distance inside it.
+        Dielectric { ir:Ior::Constant(index_of_refraction), absorption:absorption_from_transmission(transmission, distance), priority:0, film:None}
+    }
+
+    pub fn from_ior(ior: Ior) -> Self {
+        // A dispersive glass when the index of refraction varies with wavelength.
+        Dielectric { ir:ior, absorption:Color::default(), priority:0, film:None}
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_thin_film(mut self, film: ThinFilm) -> Self {
+        self.film = Some(film);
+        self
     }
 
     pub fn reflectance(&self, cosine:f64, ref_idx:f64) -> f64 {
@@ -21,12 +46,31 @@ impl Dielectric {
 impl Material for Dielectric {
     fn scatter(&self, r_in:&Ray, rec:&HitRecord, attenuation: &mut Color, scattered:&mut Ray) -> bool {
         *attenuation =  Color::new(1.0, 1.0, 1.0);
-        let refraction_ratio = if rec.front_face {1.0 / self.ir} else {self.ir};
+        let ir = self.ir.at(rec.wavelength);
+        let refraction_ratio = if rec.front_face {rec.exterior_ir / ir} else {ir / rec.exterior_ir};
 
         let unit_direction = vec3::unit_vector(r_in.direction());
         let cos_theta = rec.normal.dot(-unit_direction).min(1.0);
         let sin_theta = ((1.0 - cos_theta*cos_theta) as f64).sqrt();
 
+        if let Some(film) = &self.film {
+            // Interference in the film makes the reflectance depend on the wavelength. Pick
+            // reflection or refraction by the average, and weight each channel accordingly.
+            let (n_incident, n_base) = if rec.front_face {(rec.exterior_ir, ir)} else {(ir, rec.exterior_ir)};
+            let reflectance = film.reflectance(rec, cos_theta, n_incident, &FilmBase::Dielectric(n_base));
+            let probability = (reflectance.x + reflectance.y + reflectance.z) / 3.0;
+
+            let direction = if probability > rand::random_double() {
+                *attenuation = reflectance / probability;
+                vec3::reflect(unit_direction, rec.normal)
+            } else {
+                *attenuation = (Color::new(1.0, 1.0, 1.0) - reflectance) / (1.0 - probability);
+                vec3::refract(unit_direction, rec.normal, refraction_ratio)
+            };
+            *scattered = Ray::new_timed(rec.p, direction, r_in.time());
+            return true
+        }
+
         let cannot_refract = refraction_ratio * sin_theta > 1.0;
         let direction = if cannot_refract || self.reflectance(cos_theta, refraction_ratio) > rand::random_double() {
             vec3::reflect(unit_direction, rec.normal)
@@ -37,4 +81,21 @@ impl Material for Dielectric {
         *scattered = Ray::new_timed(rec.p, direction, r_in.time());
         true
     }
-}
\ No newline at end of file
+
+    fn is_dispersive(&self) -> bool {
+        self.ir.is_dispersive() || self.film.is_some()
+    }
+
+    fn interface(&self) -> Option<Interface> {
+        Some(Interface { ior: self.ir, priority: self.priority, absorption: self.absorption, scattering: Color::default() })
+    }
+}
+
+pub fn absorption_from_transmission(transmission: Color, distance: f64) -> Color {
+    // Invert Beer-Lambert: the coefficient that leaves `transmission` after `distance`. A
+    // channel that lets nothing through is kept just above zero, so it absorbs strongly but
+    // the coefficient stays finite.
+    assert!(distance > 0.0, "Transmission must be measured over a positive distance");
+    let coefficient = |t: f64| -t.clamp(MIN_TRANSMISSION, 1.0).ln() / distance;
+    Color::new(coefficient(transmission.x), coefficient(transmission.y), coefficient(transmission.z))
+}
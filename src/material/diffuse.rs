@@ -1,4 +1,4 @@
-use super::{Material, HitRecord, utility::{vec3, ray::Ray, color::Color}};
+use super::{Material, HitRecord, utility::{vec3, ray::Ray, color::Color, PI}};
 use crate::texture::{Texture, solid_color::SolidColor};
 use std::sync::Arc;
 
@@ -30,8 +30,18 @@ impl Material for Lambertian {
         }
         
         *scattered = Ray::new_timed(rec.p, scatter_direction, r_in.time());
-        *attenuation = self.albedo.value(rec.u, rec.v, rec.p);
+        *attenuation = self.albedo.value_at(rec);
         
         true
     }
+
+    fn eval(&self, r_in:&Ray, rec:&HitRecord, scattered:&Ray) -> Color {
+        self.albedo.value_at(rec) * self.scattering_pdf(r_in, rec, scattered)
+    }
+
+    fn scattering_pdf(&self, _r_in:&Ray, rec:&HitRecord, scattered:&Ray) -> f64 {
+        // Cosine weighted hemisphere around the normal.
+        let cos_theta = rec.normal.dot(vec3::unit_vector(scattered.direction()));
+        if cos_theta < 0.0 {0.0} else {cos_theta / PI}
+    }
 }
@@ -1,27 +1,138 @@
-use std::{sync::Arc};
+use std::sync::{Arc, OnceLock};
 
-use crate::{hittable::HitRecord, texture::{solid_color::SolidColor, Texture}, utility::{color::Color, ray::Ray, vec3::Vec3}};
+use crate::{hittable::{HitRecord, Hittable}, texture::{solid_color::SolidColor, Texture}, utility::{color::Color, distribution::Distribution2D, onb::Onb, ray::Ray, spectrum, vec3::{self, Vec3}, PI}};
 
-use super::Material;
+use super::{ies::IesProfile, Mat, Material};
+
+// Resolution at which textured emission is tabulated for light sampling.
+const DISTRIBUTION_RESOLUTION: usize = 256;
+
+// Luminous efficacy of monochromatic 555 nm light, in lumens per watt.
+const MAX_LUMINOUS_EFFICACY: f64 = 683.0;
+
+// Total power of an emitter, treating scene units as meters.
+pub enum Power {
+    Watts(f64),
+    Lumens(f64),
+}
+
+// Which faces of a surface give off light. The front is the side the outward normal points to.
+#[derive(Clone, Copy)]
+#[allow(unused)]
+pub enum EmittingSides {
+    Front,
+    Back,
+    Both,
+}
 
 pub struct DiffuseLight {
-    emit:Arc<dyn Texture>
+    emit:Arc<dyn Texture>,
+    intensity:f64,
+    sides:EmittingSides,
+    profile:Option<Arc<IesProfile>>,
+    distribution:OnceLock<Option<Distribution2D>>, // Tabulated on first use, by shapes that sample it
 }
 
 impl DiffuseLight {
-    #[allow(unused)]
     pub fn new(a:Arc<dyn Texture>) -> DiffuseLight{
-        DiffuseLight {emit:a}
+        // Textured emission (screens, signs, backlit glass). Shapes that sample lights by their
+        // texture coordinates have the texture tabulated over (u, v), so direct lighting picks
+        // its bright texels more often.
+        DiffuseLight {emit:a, intensity:1.0, sides:EmittingSides::Both, profile:None, distribution:OnceLock::new()}
     }
 
     pub fn from_color(c: Color) -> DiffuseLight {
-        DiffuseLight{emit:Arc::new(SolidColor::new(c))}
+        DiffuseLight{emit:Arc::new(SolidColor::new(c)), intensity:1.0, sides:EmittingSides::Both, profile:None, distribution:OnceLock::from(None)}
+    }
+
+    pub fn blackbody<H: Hittable>(kelvin: f64, power: Power, shape: impl Fn(Mat) -> H) -> H {
+        // A shape glowing like a blackbody with the given total power, made by shape from its
+        // material. The light emits from the front only: a Lambertian emitter radiates
+        // pi * area * radiance from each side, so dividing the power by the area of the shape
+        // keeps its output the same when it is resized. The shape is built once to measure it,
+        // then again with the emission. Color values are in watts per square meter per
+        // steradian, as seen through the photopic response.
+        let area = shape(Arc::new(DiffuseLight::from_color(Color::default()))).area();
+        assert!(area > 0.0, "A light specified by its power needs a shape with a surface");
+
+        let luminance = match power {
+            Power::Watts(w) => w * spectrum::luminous_efficiency(kelvin),
+            Power::Lumens(lm) => lm / MAX_LUMINOUS_EFFICACY,
+        } / (PI * area);
+
+        let light = DiffuseLight::from_color(spectrum::blackbody_color(kelvin) * luminance).with_sides(EmittingSides::Front);
+        shape(Arc::new(light))
+    }
+
+    pub fn with_intensity(mut self, intensity:f64) -> DiffuseLight {
+        // Scale the emission, so a texture with colors in [0,1] can light a scene.
+        self.intensity = intensity;
+        self
+    }
+
+    pub fn with_sides(mut self, sides:EmittingSides) -> DiffuseLight {
+        self.sides = sides;
+        self
+    }
+
+    pub fn with_profile(mut self, profile:Arc<IesProfile>) -> DiffuseLight {
+        // Shape the emission like a measured luminaire aimed along the surface normal, with its
+        // C0 plane along the direction of increasing u. The emitted color is reached in the
+        // profile's brightest direction.
+        self.profile = Some(profile);
+        self
+    }
+}
+
+impl DiffuseLight {
+    fn luminance_distribution(texture: &dyn Texture) -> Option<Distribution2D> {
+        let n = DISTRIBUTION_RESOLUTION;
+        let mut luminance = Vec::with_capacity(n * n);
+        for j in 0..n {
+            for i in 0..n {
+                let c = texture.value((i as f64 + 0.5) / n as f64, (j as f64 + 0.5) / n as f64, Vec3::default());
+                luminance.push(0.2126 * c.x + 0.7152 * c.y + 0.0722 * c.z);
+            }
+        }
+
+        // Keep a floor under the density, since a texel sampled dark at its center may still
+        // be bright elsewhere.
+        let mean = luminance.iter().sum::<f64>() / luminance.len() as f64;
+        if mean <= 0.0 {return None}
+        let floor = 0.01 * mean;
+        luminance.iter_mut().for_each(|l| *l = l.max(floor));
+
+        Some(Distribution2D::new(&luminance, n, n))
     }
 }
 
 impl Material for DiffuseLight {
-    fn emitted(&self, u:f64, v:f64, p:Vec3) -> Color {
-        self.emit.value(u, v, p)
+    fn emitted(&self, r_in:&Ray, rec:&HitRecord) -> Color {
+        match self.sides {
+            EmittingSides::Front if !rec.front_face => return Color::default(),
+            EmittingSides::Back if rec.front_face => return Color::default(),
+            _ => {}
+        }
+
+        let emission = self.emit.value_at(rec) * self.intensity;
+        let Some(profile) = &self.profile else {return emission};
+
+        // The normal faces the viewer, so it is the aiming direction of the face seen. Azimuths
+        // are measured from dpdu around the outward normal, so they stay put on the surface
+        // and agree between its two faces.
+        let outward = if rec.front_face {rec.normal} else {-rec.normal};
+        let tangent = rec.dpdu - outward * outward.dot(rec.dpdu);
+        let c0 = if vec3::near_zero(tangent) {Onb::build_from_w(outward).u()} else {vec3::unit_vector(tangent)};
+        let c90 = outward.cross(c0);
+
+        let direction = -vec3::unit_vector(r_in.direction());
+        let theta = direction.dot(rec.normal).clamp(-1.0, 1.0).acos().to_degrees();
+        let phi = direction.dot(c90).atan2(direction.dot(c0)).to_degrees();
+        emission * profile.intensity(theta, phi)
+    }
+
+    fn emission_distribution(&self) -> Option<&Distribution2D> {
+        self.distribution.get_or_init(|| DiffuseLight::luminance_distribution(self.emit.as_ref())).as_ref()
     }
 
     fn scatter(&self, _r_in:&Ray, _rec:&HitRecord, mut _attenuation: &mut Color, _scattered:&mut Ray) -> bool {
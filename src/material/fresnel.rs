@@ -0,0 +1,13 @@
+pub fn fresnel_dielectric(cos_theta_i: f64, etai_over_etat: f64) -> f64 {
+    // Exact Fresnel reflectance for unpolarized light at a smooth dielectric interface.
+    // Returns 1.0 under total internal reflection.
+    let cos_theta_i = cos_theta_i.clamp(0.0, 1.0);
+    let sin2_theta_t = etai_over_etat * etai_over_etat * (1.0 - cos_theta_i * cos_theta_i);
+    if sin2_theta_t >= 1.0 {return 1.0}
+
+    let cos_theta_t = (1.0 - sin2_theta_t).sqrt();
+    let r_s = (etai_over_etat * cos_theta_i - cos_theta_t) / (etai_over_etat * cos_theta_i + cos_theta_t);
+    let r_p = (cos_theta_i - etai_over_etat * cos_theta_t) / (cos_theta_i + etai_over_etat * cos_theta_t);
+
+    0.5 * (r_s * r_s + r_p * r_p)
+}
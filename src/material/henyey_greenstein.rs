@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use crate::{hittable::HitRecord, texture::{solid_color::SolidColor, Texture}, utility::{color::Color, onb::Onb, rand, ray::Ray, vec3, PI}};
+
+use super::Material;
+
+pub struct HenyeyGreenstein {
+    // Phase function for participating media. Positive asymmetry scatters light forward, negative
+    // backward and zero uniformly like Isotropic. Two lobes can be blended, e.g. a strong forward
+    // lobe with a weak backward one for clouds.
+    albedo: Arc<dyn Texture>,
+    g1: f64,
+    g2: f64,
+    blend: f64, // Weight of the second lobe
+}
+
+impl HenyeyGreenstein {
+    pub fn new(albedo: Arc<dyn Texture>, g: f64) -> Self {
+        HenyeyGreenstein { albedo, g1: g, g2: g, blend: 0.0 }
+    }
+
+    pub fn from_color(c: Color, g: f64) -> Self {
+        HenyeyGreenstein::new(Arc::new(SolidColor::new(c)), g)
+    }
+
+    pub fn two_lobe(albedo: Arc<dyn Texture>, g1: f64, g2: f64, blend: f64) -> Self {
+        HenyeyGreenstein { albedo, g1, g2, blend }
+    }
+
+    fn phase(g: f64, cos_theta: f64) -> f64 {
+        // Density over the sphere, for the angle between the incoming and scattered directions.
+        let denom = 1.0 + g * g - 2.0 * g * cos_theta;
+        (1.0 - g * g) / (4.0 * PI * denom * denom.sqrt())
+    }
+
+    fn sample_cos_theta(g: f64) -> f64 {
+        let r = rand::random_double();
+        if g.abs() < 1e-3 {return 1.0 - 2.0 * r}
+
+        let sqr = (1.0 - g * g) / (1.0 - g + 2.0 * g * r);
+        ((1.0 + g * g - sqr * sqr) / (2.0 * g)).clamp(-1.0, 1.0)
+    }
+
+    fn cos_theta(r_in: &Ray, scattered: &Ray) -> f64 {
+        vec3::unit_vector(r_in.direction()).dot(vec3::unit_vector(scattered.direction()))
+    }
+}
+
+impl Material for HenyeyGreenstein {
+    fn scatter(&self, r_in:&Ray, rec:&HitRecord, attenuation: &mut Color, scattered:&mut Ray) -> bool {
+        let g = if rand::random_double() < self.blend {self.g2} else {self.g1};
+        let cos_theta = HenyeyGreenstein::sample_cos_theta(g);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * rand::random_double();
+
+        let direction = Onb::build_from_w(r_in.direction()).local(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta);
+        *scattered = Ray::new_timed(rec.p, direction, r_in.time());
+        *attenuation = self.albedo.value_at(rec);
+        true
+    }
+
+    fn eval(&self, r_in:&Ray, rec:&HitRecord, scattered:&Ray) -> Color {
+        self.albedo.value_at(rec) * self.scattering_pdf(r_in, rec, scattered)
+    }
+
+    fn scattering_pdf(&self, r_in:&Ray, _rec:&HitRecord, scattered:&Ray) -> f64 {
+        let cos_theta = HenyeyGreenstein::cos_theta(r_in, scattered);
+        (1.0 - self.blend) * HenyeyGreenstein::phase(self.g1, cos_theta) + self.blend * HenyeyGreenstein::phase(self.g2, cos_theta)
+    }
+}
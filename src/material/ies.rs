@@ -0,0 +1,103 @@
+use std::fs;
+
+pub struct IesProfile {
+    // Type C photometry from an IESNA LM-63 file. Vertical angles are measured from the
+    // luminaire's aiming direction, horizontal angles around it, both in degrees.
+    vertical_angles: Vec<f64>,
+    horizontal_angles: Vec<f64>,
+    candela: Vec<Vec<f64>>, // One row of vertical samples per horizontal angle
+    max_candela: f64,
+}
+
+impl IesProfile {
+    pub fn new(filename: &str) -> Self {
+        match IesProfile::load(filename) {
+            Ok(profile) => profile,
+            Err(e) => panic!("Could not read {filename} into IES profile: {e}")
+        }
+    }
+
+    pub fn load(filename: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(filename).map_err(|e| e.to_string())?;
+        IesProfile::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        // Skip the keyword lines; the photometric data follows the TILT line.
+        let mut lines = text.lines();
+        let tilt = lines.by_ref()
+            .find(|l| l.trim_start().starts_with("TILT="))
+            .ok_or("missing TILT line")?;
+        let mut numbers = lines
+            .flat_map(|l| l.split(|c: char| c.is_whitespace() || c == ','))
+            .filter(|t| !t.is_empty())
+            .map(|t| t.parse::<f64>().map_err(|_| format!("bad number '{t}'")));
+        let mut next = move || numbers.next().unwrap_or(Err("file ends early".to_string()));
+
+        if tilt.trim() == "TILT=INCLUDE" {
+            // Lamp tilt factors only matter for lamps that are not mounted upright.
+            next()?;
+            let pairs = next()? as usize;
+            for _ in 0..2 * pairs {next()?;}
+        }
+
+        let _lamps = next()?;
+        let _lumens_per_lamp = next()?;
+        let multiplier = next()?;
+        let vertical_count = next()? as usize;
+        let horizontal_count = next()? as usize;
+        let photometric_type = next()?;
+        for _ in 0..7 {next()?;} // Units, luminous opening size, ballast factors, input watts
+
+        if photometric_type != 1.0 {
+            return Err("only type C photometry is supported".to_string())
+        }
+        if vertical_count == 0 || horizontal_count == 0 {
+            return Err("no candela values".to_string())
+        }
+
+        let vertical_angles = (0..vertical_count).map(|_| next()).collect::<Result<Vec<_>, _>>()?;
+        let horizontal_angles = (0..horizontal_count).map(|_| next()).collect::<Result<Vec<_>, _>>()?;
+        let candela = (0..horizontal_count)
+            .map(|_| (0..vertical_count).map(|_| next().map(|c| c * multiplier)).collect::<Result<Vec<_>, _>>())
+            .collect::<Result<Vec<_>, _>>()?;
+        let max_candela = candela.iter().flatten().cloned().fold(0.0, f64::max);
+
+        Ok(IesProfile{vertical_angles, horizontal_angles, candela, max_candela})
+    }
+
+    pub fn intensity(&self, theta: f64, phi: f64) -> f64 {
+        // Luminous intensity towards the given angles (in degrees), relative to the brightest
+        // direction of the luminaire.
+        if self.max_candela <= 0.0 {return 0.0}
+
+        // The last horizontal angle tells which symmetry the file relies on.
+        let mut phi = phi.rem_euclid(360.0);
+        let last = *self.horizontal_angles.last().unwrap();
+        if last <= 0.0 {
+            phi = 0.0;
+        } else if last <= 90.0 {
+            if phi > 180.0 {phi = 360.0 - phi;}
+            if phi > 90.0 {phi = 180.0 - phi;}
+        } else if last <= 180.0 && phi > 180.0 {
+            phi = 360.0 - phi;
+        }
+
+        let Some((v, fv)) = bracket(&self.vertical_angles, theta) else {return 0.0};
+        let (h, fh) = bracket(&self.horizontal_angles, phi).unwrap_or((self.horizontal_angles.len() - 1, 0.0));
+        let h1 = (h + 1).min(self.horizontal_angles.len() - 1);
+        let v1 = (v + 1).min(self.vertical_angles.len() - 1);
+
+        let row = |h: usize| self.candela[h][v] * (1.0 - fv) + self.candela[h][v1] * fv;
+        (row(h) * (1.0 - fh) + row(h1) * fh) / self.max_candela
+    }
+}
+
+fn bracket(angles: &[f64], x: f64) -> Option<(usize, f64)> {
+    // Index of the sample at or below x, and how far x lies towards the next one. None when x
+    // is outside the sampled range.
+    if x < angles[0] || x > angles[angles.len() - 1] {return None}
+    let i = angles.partition_point(|&a| a <= x).saturating_sub(1).min(angles.len().saturating_sub(2));
+    if angles.len() < 2 || angles[i + 1] <= angles[i] {return Some((i, 0.0))}
+    Some((i, ((x - angles[i]) / (angles[i + 1] - angles[i])).clamp(0.0, 1.0)))
+}
@@ -0,0 +1,40 @@
+use crate::utility::spectrum;
+
+#[derive(Clone, Copy)]
+pub enum Ior {
+    // Index of refraction, optionally varying with wavelength. Wavelengths are in nanometers.
+    Constant(f64),
+    Cauchy { a: f64, b: f64 },
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+impl Ior {
+    #[allow(unused)]
+    pub fn cauchy(a: f64, b: f64) -> Self {
+        // n = a + b / lambda^2, with b in square micrometers.
+        Ior::Cauchy { a, b }
+    }
+
+    pub fn sellmeier(b: [f64; 3], c: [f64; 3]) -> Self {
+        // n^2 = 1 + sum(b_i lambda^2 / (lambda^2 - c_i)), with c_i in square micrometers.
+        Ior::Sellmeier { b, c }
+    }
+
+    pub fn at(&self, wavelength: Option<f64>) -> f64 {
+        // Without a wavelength (RGB rendering), use the index at the sodium D line.
+        let wavelength = wavelength.unwrap_or(spectrum::LAMBDA_D);
+        let l2 = (wavelength * 1e-3).powi(2); // Both formulas work in micrometers.
+        match *self {
+            Ior::Constant(n) => n,
+            Ior::Cauchy { a, b } => a + b / l2,
+            Ior::Sellmeier { b, c } => {
+                let n2 = 1.0 + (0..3).map(|i| b[i] * l2 / (l2 - c[i])).sum::<f64>();
+                n2.sqrt()
+            }
+        }
+    }
+
+    pub fn is_dispersive(&self) -> bool {
+        !matches!(self, Ior::Constant(_))
+    }
+}
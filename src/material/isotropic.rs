@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use crate::{texture::{solid_color::SolidColor, Texture}, utility::{color::Color, ray::Ray, vec3}};
+use crate::{hittable::HitRecord, texture::{solid_color::SolidColor, Texture}, utility::{color::Color, ray::Ray, vec3, PI}};
 
 use super::Material;
 
@@ -22,7 +22,15 @@ impl Isotropic {
 impl Material for Isotropic {
     fn scatter(&self, r_in:&crate::utility::ray::Ray, rec:&crate::hittable::HitRecord, attenuation: &mut Color, scattered:&mut Ray) -> bool {
         *scattered = Ray::new_timed(rec.p, vec3::random_unit_vector(), r_in.time());
-        *attenuation = self.albedo.value(rec.u, rec.v, rec.p);
+        *attenuation = self.albedo.value_at(rec);
         true
     }
+
+    fn eval(&self, r_in:&Ray, rec:&HitRecord, scattered:&Ray) -> Color {
+        self.albedo.value_at(rec) * self.scattering_pdf(r_in, rec, scattered)
+    }
+
+    fn scattering_pdf(&self, _r_in:&Ray, _rec:&HitRecord, _scattered:&Ray) -> f64 {
+        1.0 / (4.0 * PI)
+    }
 }
\ No newline at end of file
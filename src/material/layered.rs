@@ -0,0 +1,93 @@
+use super::{Material, HitRecord, Mat, fresnel, microfacet::Ggx, utility::{rand, vec3::{self, Mul}, ray::Ray, color::Color}};
+
+pub struct Layered {
+    // A thin dielectric coat on top of any other material.
+    base: Mat,
+    ir: f64, // Index of Refraction of the coat
+    tint: Color, // Fraction of light that survives one perpendicular pass through the coat
+    distribution: Ggx,
+}
+
+impl Layered {
+    const MAX_INTERNAL_BOUNCES: i32 = 8;
+
+    pub fn new(base: Mat, index_of_refraction: f64, roughness: f64, tint: Color) -> Self {
+        Layered {
+            base,
+            ir: index_of_refraction,
+            tint,
+            distribution: Ggx::new(roughness),
+        }
+    }
+
+    fn coat_transmittance(&self, cosine: f64) -> Color {
+        // Slanted paths travel further through the coat.
+        let distance = 1.0 / cosine.abs().max(1e-4);
+        Color::new(self.tint.x.powf(distance), self.tint.y.powf(distance), self.tint.z.powf(distance))
+    }
+}
+
+impl Material for Layered {
+    fn scatter(&self, r_in:&Ray, rec:&HitRecord, attenuation: &mut Color, scattered:&mut Ray) -> bool {
+        let n = rec.normal;
+        let unit_direction = vec3::unit_vector(r_in.direction());
+        let wi = -unit_direction;
+        let coat_ratio = rec.exterior_ir / self.ir;
+
+        // Reflect off the rough top of the coat with probability given by the Fresnel term, as
+        // in RoughDielectric.
+        let m = self.distribution.sample_normal(n);
+        let cos_i_m = wi.dot(m);
+        if cos_i_m > 0.0 && fresnel::fresnel_dielectric(cos_i_m, coat_ratio) > rand::random_double() {
+            let direction = vec3::reflect(unit_direction, m);
+            if direction.dot(n) <= 0.0 {return false}
+
+            let weight = cos_i_m * self.distribution.g(wi, direction, m, n) / (wi.dot(n) * m.dot(n)).abs();
+            *attenuation = Color::new(weight, weight, weight);
+            *scattered = Ray::new_timed(rec.p, direction, r_in.time());
+            return true
+        }
+
+        // Otherwise the light refracts into the coat and bounces between the base and the
+        // underside of the coat until it escapes. Each exit attempt succeeds with probability
+        // one minus the Fresnel reflectance, so only the base and the coat tint weight the path.
+        let mut inside = Ray::new_timed(rec.p, vec3::refract(unit_direction, n, coat_ratio), r_in.time());
+        let mut throughput = self.coat_transmittance(inside.direction().dot(n));
+
+        for _ in 0..Layered::MAX_INTERNAL_BOUNCES {
+            let mut base_attenuation = Color::default();
+            let mut base_scattered = Ray::default();
+            if !self.base.scatter(&inside, rec, &mut base_attenuation, &mut base_scattered) {
+                return false
+            }
+            throughput = throughput.mul(base_attenuation);
+
+            let up = vec3::unit_vector(base_scattered.direction());
+            let cos_up = up.dot(n);
+            if cos_up <= 0.0 {
+                // The base transmitted the light, so it never reaches the coat again.
+                *attenuation = throughput;
+                *scattered = base_scattered;
+                return true
+            }
+
+            throughput = throughput.mul(self.coat_transmittance(cos_up));
+            if fresnel::fresnel_dielectric(cos_up, 1.0 / coat_ratio) > rand::random_double() {
+                // Internal reflection sends the light back down to the base.
+                inside = Ray::new_timed(rec.p, vec3::reflect(up, n), r_in.time());
+                throughput = throughput.mul(self.coat_transmittance(cos_up));
+                continue
+            }
+
+            *attenuation = throughput;
+            *scattered = Ray::new_timed(rec.p, vec3::refract(up, -n, 1.0 / coat_ratio), r_in.time());
+            return true
+        }
+
+        false
+    }
+
+    fn emitted(&self, r_in:&Ray, rec:&HitRecord) -> Color {
+        self.base.emitted(r_in, rec)
+    }
+}
@@ -0,0 +1,95 @@
+use crate::hittable::constant_medium::ConstantMedium;
+use crate::utility::{color::Color, rand, vec3::Mul};
+
+use super::ior::Ior;
+
+#[derive(Clone, Copy)]
+pub struct Interface {
+    // Describes the medium enclosed by a dielectric surface.
+    pub ior: Ior,
+    pub priority: i32,
+    pub absorption: Color,
+    pub scattering: Color, // Zero for media that only absorb
+}
+
+#[derive(Clone, Copy)]
+struct Entry {
+    id: usize,
+    interface: Interface,
+}
+
+#[derive(Clone, Default)]
+pub struct MediumStack {
+    // The media a path is currently inside of, in the order they were entered.
+    entries: Vec<Entry>,
+}
+
+impl MediumStack {
+    fn top(&self) -> Option<&Entry> {
+        // The medium that owns the current region is the one with the highest priority. On a
+        // tie, the one entered most recently wins.
+        self.entries.iter().max_by_key(|e| e.interface.priority)
+    }
+
+    pub fn priority(&self) -> i32 {
+        self.top().map_or(i32::MIN, |e| e.interface.priority)
+    }
+
+    pub fn ir(&self, wavelength: Option<f64>) -> f64 {
+        // Paths outside of every dielectric travel through air.
+        self.top().map_or(1.0, |e| e.interface.ior.at(wavelength))
+    }
+
+    pub fn transmittance(&self, distance: f64) -> Color {
+        // Beer-Lambert attenuation over a segment of the given length in the current medium.
+        match self.top() {
+            None => Color::new(1.0, 1.0, 1.0),
+            Some(e) => Color::new(
+                (-e.interface.absorption.x * distance).exp(),
+                (-e.interface.absorption.y * distance).exp(),
+                (-e.interface.absorption.z * distance).exp(),
+            ),
+        }
+    }
+
+    pub fn sample_distance(&self, max_distance: f64) -> (f64, Color) {
+        // Sample where the path next interacts with the current medium, up to the surface at
+        // max_distance. Returns the distance and the path weight for that event. Paths that
+        // reach max_distance pass through to the surface.
+        let Some(e) = self.top() else {return (max_distance, Color::new(1.0, 1.0, 1.0))};
+        let scattering = e.interface.scattering;
+        if scattering.norm2() == 0.0 {
+            return (max_distance, self.transmittance(max_distance))
+        }
+
+        // The density differs per channel, so pick one to sample the free flight with and
+        // weight by the average density over all three.
+        let extinction = e.interface.absorption + scattering;
+        let channel = rand::random_int_range(0, 3) as usize;
+        let distance = ConstantMedium::free_flight_distance(-1.0 / extinction[channel]);
+
+        let d = distance.min(max_distance);
+        let tr = Color::new((-extinction.x * d).exp(), (-extinction.y * d).exp(), (-extinction.z * d).exp());
+        if distance < max_distance {
+            let pdf = extinction.mul(tr).dot(Color::new(1.0, 1.0, 1.0)) / 3.0;
+            (distance, scattering.mul(tr) / pdf)
+        } else {
+            let pdf = tr.dot(Color::new(1.0, 1.0, 1.0)) / 3.0;
+            (max_distance, tr / pdf)
+        }
+    }
+
+    pub fn entered(&self, id: usize, interface: Interface) -> MediumStack {
+        let mut stack = self.clone();
+        stack.entries.push(Entry { id, interface });
+        stack
+    }
+
+    pub fn exited(&self, id: usize) -> MediumStack {
+        let mut stack = self.clone();
+        if let Some(i) = stack.entries.iter().rposition(|e| e.id == id) {
+            stack.entries.remove(i);
+        }
+        stack
+    }
+}
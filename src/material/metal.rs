@@ -1,22 +1,39 @@
-use super::{Material, HitRecord, utility::{vec3, ray::Ray, color::Color}};
+use super::{Material, HitRecord, thin_film::{FilmBase, ThinFilm}, utility::{vec3, ray::Ray, color::Color}};
 
 pub struct Metal {
     albedo:Color,
     fuzz: f64,
+    film: Option<ThinFilm>, // Oxide layer or other coating causing iridescence
 }
 
 impl Metal {
     pub fn new(a:Color, f:f64) -> Self {
         let fuzz = if f < 1.0 {f} else {1.0};
-        Metal { albedo:a, fuzz }
+        Metal { albedo:a, fuzz, film:None }
+    }
+
+    pub fn with_thin_film(mut self, film: ThinFilm) -> Self {
+        self.film = Some(film);
+        self
     }
 }
 
 impl Material for Metal {
     fn scatter(&self, r_in:&Ray, rec:&HitRecord, attenuation: &mut Color, scattered:&mut Ray) -> bool {
-        let reflected = vec3::reflect(vec3::unit_vector(r_in.direction()), rec.normal);
+        let unit_direction = vec3::unit_vector(r_in.direction());
+        let reflected = vec3::reflect(unit_direction, rec.normal);
         *scattered = Ray::new_timed(rec.p, reflected + vec3::random_unit_vector()*self.fuzz, r_in.time());
-        *attenuation = self.albedo;
+        *attenuation = match &self.film {
+            None => self.albedo,
+            Some(film) => {
+                let cos_theta = rec.normal.dot(-unit_direction).min(1.0);
+                film.reflectance(rec, cos_theta, rec.exterior_ir, &FilmBase::Conductor(self.albedo))
+            },
+        };
         scattered.direction().dot(rec.normal) > 0.0
     }
+
+    fn is_dispersive(&self) -> bool {
+        self.film.is_some()
+    }
 }
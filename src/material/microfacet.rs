@@ -0,0 +1,55 @@
+use crate::utility::{onb::Onb, rand, vec3::Vec3, PI};
+
+pub struct Ggx {
+    alpha: f64,
+}
+
+impl Ggx {
+    pub fn new(roughness: f64) -> Self {
+        // Remap the perceptual roughness to the GGX width. A tiny lower bound keeps the
+        // distribution finite for "almost smooth" surfaces.
+        Ggx { alpha: (roughness * roughness).max(1e-4) }
+    }
+
+    pub fn d(&self, m: Vec3, n: Vec3) -> f64 {
+        // GGX normal distribution function for the microfacet normal m.
+        let cos_m = m.dot(n);
+        if cos_m <= 0.0 {return 0.0}
+
+        let a2 = self.alpha * self.alpha;
+        let denom = cos_m * cos_m * (a2 - 1.0) + 1.0;
+        a2 / (PI * denom * denom)
+    }
+
+    pub fn g1(&self, v: Vec3, m: Vec3, n: Vec3) -> f64 {
+        // Smith shadowing-masking term for a single direction.
+        let cos_v = v.dot(n);
+        if v.dot(m) / cos_v <= 0.0 {return 0.0}
+
+        let cos2 = cos_v * cos_v;
+        let tan2 = (1.0 - cos2).max(0.0) / cos2;
+        2.0 / (1.0 + (1.0 + self.alpha * self.alpha * tan2).sqrt())
+    }
+
+    pub fn g(&self, wi: Vec3, wo: Vec3, m: Vec3, n: Vec3) -> f64 {
+        self.g1(wi, m, n) * self.g1(wo, m, n)
+    }
+
+    pub fn sample_normal(&self, n: Vec3) -> Vec3 {
+        // Sample a microfacet normal proportionally to D(m) |m.n|.
+        let r1 = rand::random_double();
+        let r2 = rand::random_double();
+
+        let tan2_theta = self.alpha * self.alpha * r1 / (1.0 - r1);
+        let cos_theta = 1.0 / (1.0 + tan2_theta).sqrt();
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * r2;
+
+        Onb::build_from_w(n).local(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta)
+    }
+
+    pub fn pdf(&self, m: Vec3, n: Vec3) -> f64 {
+        // Density of sample_normal() with respect to solid angle around m.
+        self.d(m, n) * m.dot(n).abs()
+    }
+}
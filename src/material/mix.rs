@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use super::{Material, HitRecord, Mat, utility::{rand, ray::Ray, color::Color}};
+use crate::texture::Texture;
+
+pub struct MixMaterial {
+    // Blends two materials by a mask texture: where the mask (red channel) is 0 the surface is
+    // entirely the first material, where it is 1 entirely the second.
+    first: Mat,
+    second: Mat,
+    mask: Arc<dyn Texture>,
+}
+
+impl MixMaterial {
+    pub fn new(first: Mat, second: Mat, mask: Arc<dyn Texture>) -> Self {
+        MixMaterial { first, second, mask }
+    }
+
+    fn weight(&self, rec: &HitRecord) -> f64 {
+        self.mask.value_at(rec).x.clamp(0.0, 1.0)
+    }
+}
+
+impl Material for MixMaterial {
+    fn scatter(&self, r_in:&Ray, rec:&HitRecord, attenuation: &mut Color, scattered:&mut Ray) -> bool {
+        self.scatter_sampled(r_in, rec, attenuation, scattered).is_some()
+    }
+
+    fn scatter_sampled(&self, r_in:&Ray, rec:&HitRecord, attenuation: &mut Color, scattered:&mut Ray) -> Option<f64> {
+        // Pick one of the materials with probability equal to its share of the blend and let it
+        // sample the direction.
+        let t = self.weight(rec);
+        let picked = if rand::random_double() < t {&self.second} else {&self.first};
+        let picked_pdf = picked.scatter_sampled(r_in, rec, attenuation, scattered)?;
+
+        // A delta lobe keeps the picked material's own weight, which is already the unbiased
+        // estimate, and tells the camera not to weight it against light sampling.
+        if picked_pdf <= 0.0 {
+            return Some(0.0)
+        }
+
+        // Otherwise the direction could also have been produced by the other material, so
+        // weight by the blended BSDF over the blended density (one-sample MIS).
+        let pdf = self.scattering_pdf(r_in, rec, scattered);
+        if pdf > 0.0 {
+            *attenuation = self.eval(r_in, rec, scattered) / pdf;
+        }
+        Some(pdf)
+    }
+
+    fn eval(&self, r_in:&Ray, rec:&HitRecord, scattered:&Ray) -> Color {
+        let t = self.weight(rec);
+        self.first.eval(r_in, rec, scattered) * (1.0 - t) + self.second.eval(r_in, rec, scattered) * t
+    }
+
+    fn scattering_pdf(&self, r_in:&Ray, rec:&HitRecord, scattered:&Ray) -> f64 {
+        let t = self.weight(rec);
+        self.first.scattering_pdf(r_in, rec, scattered) * (1.0 - t) + self.second.scattering_pdf(r_in, rec, scattered) * t
+    }
+
+    fn emitted(&self, r_in:&Ray, rec:&HitRecord) -> Color {
+        let t = self.weight(rec);
+        self.first.emitted(r_in, rec) * (1.0 - t) + self.second.emitted(r_in, rec) * t
+    }
+}
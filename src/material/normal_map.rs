@@ -0,0 +1,131 @@
+use std::sync::Arc;
+
+use super::{Material, HitRecord, Mat, medium_stack::Interface, utility::{vec3::{self, Vec3}, ray::Ray, color::Color, distribution::Distribution2D}};
+use crate::texture::Texture;
+
+enum Source {
+    Normals(Arc<dyn Texture>), // Tangent-space normals packed into [0, 1] colors
+    Heights(Arc<dyn Texture>), // Height above the surface, from the red channel
+}
+
+pub struct NormalMap {
+    // Wraps another material and shades it with a normal perturbed by a texture, so flat
+    // geometry looks embossed. The surface itself stays where it is.
+    base: Mat,
+    source: Source,
+    strength: f64,
+}
+
+impl NormalMap {
+    pub fn new(base: Mat, normals: Arc<dyn Texture>) -> Self {
+        // The map should be read as data, not color: load image maps with ColorSpace::Linear.
+        NormalMap { base, source: Source::Normals(normals), strength: 1.0 }
+    }
+
+    pub fn bump(base: Mat, heights: Arc<dyn Texture>, scale: f64) -> Self {
+        // A height of 1 raises the surface by scale, in world units.
+        NormalMap { base, source: Source::Heights(heights), strength: scale }
+    }
+
+    #[allow(unused)]
+    pub fn with_strength(mut self, strength: f64) -> Self {
+        // Exaggerates (above 1) or flattens (below 1) the relief.
+        self.strength = strength;
+        self
+    }
+
+    fn perturbed_normal(&self, rec: &HitRecord, n: Vec3) -> Option<Vec3> {
+        // The perturbed version of the outward normal n, or None where the surface has no
+        // tangent frame.
+        if vec3::near_zero(rec.dpdu) || vec3::near_zero(rec.dpdv) {return None}
+
+        match &self.source {
+            Source::Normals(normals) => {
+                // Gram-Schmidt the tangent against the normal; the bitangent follows dpdv so
+                // mirrored texture coordinates keep green pointing towards increasing v.
+                let tangent = vec3::unit_vector(rec.dpdu - n * n.dot(rec.dpdu));
+                let mut bitangent = n.cross(tangent);
+                if bitangent.dot(rec.dpdv) < 0.0 {bitangent = -bitangent}
+
+                let c = normals.value_at(rec) * 2.0 - Color::new(1.0, 1.0, 1.0);
+                Some(tangent * (c.x * self.strength) + bitangent * (c.y * self.strength) + n * c.z.max(0.0))
+            }
+            Source::Heights(heights) => {
+                // Finite differences of the displaced surface p + n * h(u, v) (Blinn 1978), with
+                // steps about the size of the pixel footprint.
+                let (du, dv) = match rec.differentials {
+                    Some(d) => (
+                        0.5 * (d.duvdx.0.abs() + d.duvdy.0.abs()),
+                        0.5 * (d.duvdx.1.abs() + d.duvdy.1.abs()),
+                    ),
+                    None => (0.0, 0.0),
+                };
+                let du = if du > 0.0 {du} else {0.0005};
+                let dv = if dv > 0.0 {dv} else {0.0005};
+
+                let height = |u: f64, v: f64, p: Vec3| {
+                    let mut shifted = rec.clone();
+                    (shifted.u, shifted.v, shifted.p) = (u, v, p);
+                    heights.value_at(&shifted).x * self.strength
+                };
+                let h = height(rec.u, rec.v, rec.p);
+                let dhdu = (height(rec.u + du, rec.v, rec.p + rec.dpdu * du) - h) / du;
+                let dhdv = (height(rec.u, rec.v + dv, rec.p + rec.dpdv * dv) - h) / dv;
+
+                let bumped = (rec.dpdu + n * dhdu).cross(rec.dpdv + n * dhdv);
+                Some(if bumped.dot(n) < 0.0 {-bumped} else {bumped})
+            }
+        }
+    }
+
+    fn shading_record(&self, r_in: &Ray, rec: &HitRecord) -> HitRecord {
+        // The hit record the base material sees, with the perturbed normal on the side the ray
+        // arrived from. Where the perturbed normal would face away from the ray, the geometric
+        // one is kept instead.
+        let outward = if rec.front_face {rec.normal} else {-rec.normal};
+        let Some(perturbed) = self.perturbed_normal(rec, outward) else {return rec.clone()};
+        if vec3::near_zero(perturbed) {return rec.clone()}
+
+        let perturbed = vec3::unit_vector(perturbed);
+        let normal = if rec.front_face {perturbed} else {-perturbed};
+        if r_in.direction().dot(normal) >= 0.0 {return rec.clone()}
+
+        let mut shading = rec.clone();
+        shading.normal = normal;
+        shading
+    }
+}
+
+impl Material for NormalMap {
+    fn scatter(&self, r_in:&Ray, rec:&HitRecord, attenuation: &mut Color, scattered:&mut Ray) -> bool {
+        self.base.scatter(r_in, &self.shading_record(r_in, rec), attenuation, scattered)
+    }
+
+    fn scatter_sampled(&self, r_in:&Ray, rec:&HitRecord, attenuation: &mut Color, scattered:&mut Ray) -> Option<f64> {
+        self.base.scatter_sampled(r_in, &self.shading_record(r_in, rec), attenuation, scattered)
+    }
+
+    fn eval(&self, r_in:&Ray, rec:&HitRecord, scattered:&Ray) -> Color {
+        self.base.eval(r_in, &self.shading_record(r_in, rec), scattered)
+    }
+
+    fn scattering_pdf(&self, r_in:&Ray, rec:&HitRecord, scattered:&Ray) -> f64 {
+        self.base.scattering_pdf(r_in, &self.shading_record(r_in, rec), scattered)
+    }
+
+    fn emitted(&self, r_in:&Ray, rec:&HitRecord) -> Color {
+        self.base.emitted(r_in, rec)
+    }
+
+    fn emission_distribution(&self) -> Option<&Distribution2D> {
+        self.base.emission_distribution()
+    }
+
+    fn is_dispersive(&self) -> bool {
+        self.base.is_dispersive()
+    }
+
+    fn interface(&self) -> Option<Interface> {
+        self.base.interface()
+    }
+}
@@ -0,0 +1,260 @@
+use std::sync::Arc;
+
+use super::{Material, HitRecord, fresnel, microfacet::Ggx, utility::{rand, vec3::{self, Vec3}, ray::Ray, color::Color, PI}};
+use crate::texture::{Texture, solid_color::SolidColor};
+
+pub struct Principled {
+    // Every input is a texture. Scalar inputs read the red channel of theirs.
+    pub base_color: Arc<dyn Texture>,
+    pub metallic: Arc<dyn Texture>,
+    pub roughness: Arc<dyn Texture>,
+    pub specular: Arc<dyn Texture>, // 0.5 matches a common dielectric with an IOR of 1.5
+    pub specular_tint: Arc<dyn Texture>,
+    pub sheen: Arc<dyn Texture>,
+    pub clearcoat: Arc<dyn Texture>,
+    pub transmission: Arc<dyn Texture>,
+    pub emission: Arc<dyn Texture>,
+}
+
+struct Lobes {
+    // The material inputs evaluated at one hit point.
+    base_color: Color,
+    metallic: f64,
+    specular_f0: Color,
+    ior: f64,
+    sheen: f64,
+    clearcoat: f64,
+    transmission: f64,
+    roughness: f64,
+    specular: Ggx,
+}
+
+impl Principled {
+    pub fn new(base_color: Arc<dyn Texture>) -> Self {
+        Principled {
+            base_color,
+            metallic: Arc::new(SolidColor::from_value(0.0)),
+            roughness: Arc::new(SolidColor::from_value(0.5)),
+            specular: Arc::new(SolidColor::from_value(0.5)),
+            specular_tint: Arc::new(SolidColor::from_value(0.0)),
+            sheen: Arc::new(SolidColor::from_value(0.0)),
+            clearcoat: Arc::new(SolidColor::from_value(0.0)),
+            transmission: Arc::new(SolidColor::from_value(0.0)),
+            emission: Arc::new(SolidColor::new(Color::default())),
+        }
+    }
+
+    pub fn from_color(c: Color) -> Self {
+        Principled::new(Arc::new(SolidColor::new(c)))
+    }
+
+    fn lobes(&self, rec: &HitRecord) -> Lobes {
+        let scalar = |t: &Arc<dyn Texture>| t.value_at(rec).x.clamp(0.0, 1.0);
+
+        let base_color = self.base_color.value_at(rec);
+        let metallic = scalar(&self.metallic);
+        let roughness = scalar(&self.roughness);
+        let specular = scalar(&self.specular);
+        let specular_tint = scalar(&self.specular_tint);
+
+        // Dielectric reflectance at normal incidence, optionally tinted towards the base hue,
+        // blended into the base color for metals.
+        let luminance = luminance(base_color);
+        let tint = if luminance > 0.0 {base_color / luminance} else {Color::new(1.0, 1.0, 1.0)};
+        let dielectric_f0 = lerp(Color::new(1.0, 1.0, 1.0), tint, specular_tint) * (0.08 * specular);
+        let specular_f0 = lerp(dielectric_f0, base_color, metallic);
+
+        let f0 = (0.08 * specular).clamp(1e-4, 0.99).sqrt();
+        let ior = (1.0 + f0) / (1.0 - f0);
+
+        Lobes {
+            base_color,
+            metallic,
+            specular_f0,
+            ior,
+            sheen: scalar(&self.sheen),
+            clearcoat: scalar(&self.clearcoat),
+            transmission: scalar(&self.transmission),
+            roughness,
+            specular: Ggx::new(roughness),
+        }
+    }
+}
+
+impl Lobes {
+    fn clearcoat_distribution() -> Ggx {
+        Ggx::new(0.1)
+    }
+
+    fn lobe_weights(&self, cos_i: f64) -> [f64; 4] {
+        // Probabilities of sampling the diffuse, specular, clearcoat and transmission lobes,
+        // roughly proportional to the energy each one reflects.
+        let specular = luminance(schlick(self.specular_f0, cos_i)).max(0.05);
+        let dielectric = (1.0 - self.metallic) * (1.0 - specular);
+        let weights = [
+            dielectric * (1.0 - self.transmission),
+            specular,
+            0.25 * self.clearcoat,
+            dielectric * self.transmission,
+        ];
+        let total: f64 = weights.iter().sum();
+        weights.map(|w| w / total)
+    }
+
+    fn eta(&self, rec: &HitRecord) -> f64 {
+        // Ratio of the indices of refraction on the incident and transmitted sides.
+        if rec.front_face {rec.exterior_ir / self.ior} else {self.ior / rec.exterior_ir}
+    }
+
+    fn eval(&self, rec: &HitRecord, wi: Vec3, wo: Vec3) -> Color {
+        // BSDF times the cosine of the outgoing direction.
+        let n = rec.normal;
+        let cos_i = wi.dot(n);
+        let cos_o = wo.dot(n);
+        if cos_i <= 0.0 {return Color::default()}
+
+        if cos_o > 0.0 {
+            let h = vec3::unit_vector(wi + wo);
+            let cos_d = wi.dot(h);
+
+            // Disney diffuse with retro-reflection at grazing angles, plus sheen.
+            let fd90 = 0.5 + 2.0 * self.roughness * cos_d * cos_d;
+            let fd = (1.0 + (fd90 - 1.0) * (1.0 - cos_o).powi(5)) * (1.0 + (fd90 - 1.0) * (1.0 - cos_i).powi(5));
+            let sheen = self.sheen * (1.0 - cos_d).powi(5);
+            let diffuse = (self.base_color * (fd / PI) + Color::new(sheen, sheen, sheen))
+                * ((1.0 - self.metallic) * (1.0 - self.transmission));
+
+            let specular = schlick(self.specular_f0, cos_d)
+                * (self.specular.d(h, n) * self.specular.g(wi, wo, h, n) / (4.0 * cos_i * cos_o));
+
+            let coat = Lobes::clearcoat_distribution();
+            let clearcoat = 0.25 * self.clearcoat * schlick_scalar(0.04, cos_d)
+                * coat.d(h, n) * coat.g(wi, wo, h, n) / (4.0 * cos_i * cos_o);
+
+            return (diffuse + specular + Color::new(clearcoat, clearcoat, clearcoat)) * cos_o
+        }
+
+        // Rough dielectric transmission (Walter et al. 2007), tinted by the base color.
+        let weight = (1.0 - self.metallic) * self.transmission;
+        if weight <= 0.0 {return Color::default()}
+
+        let eta = 1.0 / self.eta(rec);
+        let mut h = vec3::unit_vector(-(wi + wo * eta));
+        if h.dot(n) < 0.0 {h = -h}
+        let cos_i_h = wi.dot(h);
+        let cos_o_h = wo.dot(h);
+        if cos_i_h <= 0.0 || cos_o_h >= 0.0 {return Color::default()}
+
+        let denom = cos_i_h + eta * cos_o_h;
+        let f = fresnel::fresnel_dielectric(cos_i_h, 1.0 / eta);
+        let btdf = eta * eta * cos_i_h * cos_o_h.abs() * (1.0 - f)
+            * self.specular.d(h, n) * self.specular.g(wi, wo, h, n)
+            / (cos_i * cos_o.abs() * denom * denom);
+
+        self.base_color * (weight * btdf * cos_o.abs())
+    }
+
+    fn pdf(&self, rec: &HitRecord, wi: Vec3, wo: Vec3) -> f64 {
+        let n = rec.normal;
+        let cos_i = wi.dot(n);
+        let cos_o = wo.dot(n);
+        let [p_diffuse, p_specular, p_clearcoat, p_transmission] = self.lobe_weights(cos_i);
+
+        if cos_o > 0.0 {
+            let h = vec3::unit_vector(wi + wo);
+            let jacobian = 1.0 / (4.0 * wo.dot(h).abs());
+            return p_diffuse * cos_o / PI
+                + p_specular * self.specular.pdf(h, n) * jacobian
+                + p_clearcoat * Lobes::clearcoat_distribution().pdf(h, n) * jacobian
+        }
+
+        if p_transmission <= 0.0 {return 0.0}
+        let eta = 1.0 / self.eta(rec);
+        let mut h = vec3::unit_vector(-(wi + wo * eta));
+        if h.dot(n) < 0.0 {h = -h}
+        let cos_o_h = wo.dot(h);
+        let denom = wi.dot(h) + eta * cos_o_h;
+
+        p_transmission * self.specular.pdf(h, n) * eta * eta * cos_o_h.abs() / (denom * denom)
+    }
+
+    fn sample(&self, rec: &HitRecord, unit_direction: Vec3) -> Option<Vec3> {
+        // Pick a lobe, then sample a direction from it.
+        let n = rec.normal;
+        let [p_diffuse, p_specular, p_clearcoat, _] = self.lobe_weights(-unit_direction.dot(n));
+        let r = rand::random_double();
+
+        if r < p_diffuse {
+            let direction = n + vec3::random_unit_vector();
+            return Some(if vec3::near_zero(direction) {n} else {direction})
+        }
+        if r < p_diffuse + p_specular + p_clearcoat {
+            let m = if r < p_diffuse + p_specular {
+                self.specular.sample_normal(n)
+            } else {
+                Lobes::clearcoat_distribution().sample_normal(n)
+            };
+            let direction = vec3::reflect(unit_direction, m);
+            return if direction.dot(n) > 0.0 {Some(direction)} else {None}
+        }
+
+        let m = self.specular.sample_normal(n);
+        let eta = self.eta(rec);
+        let cos_i = -unit_direction.dot(m);
+        if cos_i <= 0.0 || eta * eta * (1.0 - cos_i * cos_i) >= 1.0 {return None}
+        let direction = vec3::refract(unit_direction, m, eta);
+        if direction.dot(n) < 0.0 {Some(direction)} else {None}
+    }
+}
+
+impl Material for Principled {
+    fn scatter(&self, r_in:&Ray, rec:&HitRecord, attenuation: &mut Color, scattered:&mut Ray) -> bool {
+        // One-sample MIS: sample a single lobe, but weight by the combined density of all lobes.
+        let lobes = self.lobes(rec);
+        let unit_direction = vec3::unit_vector(r_in.direction());
+        let wi = -unit_direction;
+
+        let direction = match lobes.sample(rec, unit_direction) {
+            Some(d) => vec3::unit_vector(d),
+            None => return false,
+        };
+
+        let pdf = lobes.pdf(rec, wi, direction);
+        if pdf <= 0.0 {return false}
+
+        *attenuation = lobes.eval(rec, wi, direction) / pdf;
+        *scattered = Ray::new_timed(rec.p, direction, r_in.time());
+        true
+    }
+
+    fn eval(&self, r_in:&Ray, rec:&HitRecord, scattered:&Ray) -> Color {
+        let wi = -vec3::unit_vector(r_in.direction());
+        self.lobes(rec).eval(rec, wi, vec3::unit_vector(scattered.direction()))
+    }
+
+    fn scattering_pdf(&self, r_in:&Ray, rec:&HitRecord, scattered:&Ray) -> f64 {
+        let wi = -vec3::unit_vector(r_in.direction());
+        self.lobes(rec).pdf(rec, wi, vec3::unit_vector(scattered.direction()))
+    }
+
+    fn emitted(&self, _r_in:&Ray, rec:&HitRecord) -> Color {
+        self.emission.value_at(rec)
+    }
+}
+
+fn luminance(c: Color) -> f64 {
+    0.2126 * c.x + 0.7152 * c.y + 0.0722 * c.z
+}
+
+fn lerp(a: Color, b: Color, t: f64) -> Color {
+    a * (1.0 - t) + b * t
+}
+
+fn schlick(f0: Color, cosine: f64) -> Color {
+    let w = (1.0 - cosine.clamp(0.0, 1.0)).powi(5);
+    f0 + (Color::new(1.0, 1.0, 1.0) - f0) * w
+}
+
+fn schlick_scalar(f0: f64, cosine: f64) -> f64 {
+    f0 + (1.0 - f0) * (1.0 - cosine.clamp(0.0, 1.0)).powi(5)
+}
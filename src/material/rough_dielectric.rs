@@ -0,0 +1,75 @@
+use super::{Material, HitRecord, dielectric::absorption_from_transmission, fresnel, ior::Ior, medium_stack::Interface, microfacet::Ggx, utility::{rand, vec3, ray::Ray, color::Color}};
+
+
+pub struct RoughDielectric {
+    ir: f64, // Index of Refraction
+    absorption: Color, // Beer-Lambert absorption coefficient per unit length
+    priority: i32, // Which medium wins where nested dielectrics overlap
+    distribution: Ggx,
+}
+
+impl RoughDielectric {
+    pub fn new(index_of_refraction: f64, roughness: f64) -> Self {
+        RoughDielectric {
+            ir: index_of_refraction,
+            absorption: Color::default(),
+            priority: 0,
+            distribution: Ggx::new(roughness),
+        }
+    }
+
+    pub fn from_transmission(index_of_refraction: f64, roughness: f64, transmission: Color, distance: f64) -> Self {
+        // The glass lets through the given color after light travels the given distance inside it.
+        RoughDielectric {
+            ir: index_of_refraction,
+            absorption: absorption_from_transmission(transmission, distance),
+            priority: 0,
+            distribution: Ggx::new(roughness),
+        }
+    }
+
+    #[allow(unused)]
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+impl Material for RoughDielectric {
+    fn scatter(&self, r_in:&Ray, rec:&HitRecord, attenuation: &mut Color, scattered:&mut Ray) -> bool {
+        // Walter et al. 2007: sample a microfacet normal, then reflect or refract about it
+        // with probability given by the exact Fresnel term.
+        let refraction_ratio = if rec.front_face {rec.exterior_ir / self.ir} else {self.ir / rec.exterior_ir};
+
+        let unit_direction = vec3::unit_vector(r_in.direction());
+        let wi = -unit_direction;
+        let m = self.distribution.sample_normal(rec.normal);
+        let cos_i_m = wi.dot(m);
+        if cos_i_m <= 0.0 {return false}
+
+        // Total internal reflection gives a reflectance of one, so the ray always reflects.
+        let reflectance = fresnel::fresnel_dielectric(cos_i_m, refraction_ratio);
+        let reflect = reflectance > rand::random_double();
+        let direction = if reflect {
+            vec3::reflect(unit_direction, m)
+        } else {
+            vec3::refract(unit_direction, m, refraction_ratio)
+        };
+
+        // Reject directions that ended up on the wrong side of the macro surface.
+        let cos_o_n = direction.dot(rec.normal);
+        if reflect != (cos_o_n > 0.0) {return false}
+
+        let cos_i_n = wi.dot(rec.normal);
+        let cos_m_n = m.dot(rec.normal);
+        let weight = cos_i_m * self.distribution.g(wi, direction, m, rec.normal) / (cos_i_n * cos_m_n).abs();
+
+        *attenuation = Color::new(weight, weight, weight);
+        *scattered = Ray::new_timed(rec.p, direction, r_in.time());
+        true
+    }
+
+    fn interface(&self) -> Option<Interface> {
+        Some(Interface { ior: Ior::Constant(self.ir), priority: self.priority, absorption: self.absorption, scattering: Color::default() })
+    }
+}
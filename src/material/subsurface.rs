@@ -0,0 +1,55 @@
+use super::{Material, HitRecord, dielectric::Dielectric, ior::Ior, medium_stack::Interface, utility::{ray::Ray, color::Color}};
+
+pub struct Subsurface {
+    // A smooth dielectric boundary around a scattering interior. Light that refracts inside
+    // random walks through the medium until it finds its way back out.
+    boundary: Dielectric,
+    interface: Interface, // The interior medium, ranked against others like a dielectric's
+}
+
+impl Subsurface {
+    pub fn new(albedo: Color, mean_free_path: Color, index_of_refraction: f64) -> Self {
+        // The albedo is the color the surface should have after all the internal bounces, and
+        // the mean free path (per channel, in world units) how far light travels between them.
+        let single_scatter = |a: f64| {
+            // Invert the multiple scattering albedo (Chiang et al. 2016).
+            let s = 4.09712 + 4.20863 * a - (9.59217 + 41.6808 * a + 17.7126 * a * a).sqrt();
+            1.0 - s * s
+        };
+        let extinction = |mfp: f64| 1.0 / mfp.max(1e-6);
+
+        let albedo = Color::new(single_scatter(albedo.x), single_scatter(albedo.y), single_scatter(albedo.z));
+        let extinction = Color::new(extinction(mean_free_path.x), extinction(mean_free_path.y), extinction(mean_free_path.z));
+        let scattering = Color::new(albedo.x * extinction.x, albedo.y * extinction.y, albedo.z * extinction.z);
+
+        let ior = Ior::Constant(index_of_refraction);
+        Subsurface {
+            boundary: Dielectric::from_ior(ior),
+            interface: Interface { ior, priority: 0, absorption: extinction - scattering, scattering },
+        }
+    }
+
+    #[allow(unused)]
+    pub fn with_ior(mut self, ior: Ior) -> Self {
+        // A dispersive boundary when the index of refraction varies with wavelength.
+        self.boundary = Dielectric::from_ior(ior);
+        self.interface.ior = ior;
+        self
+    }
+
+    #[allow(unused)]
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.interface.priority = priority;
+        self
+    }
+}
+
+impl Material for Subsurface {
+    fn scatter(&self, r_in:&Ray, rec:&HitRecord, attenuation: &mut Color, scattered:&mut Ray) -> bool {
+        self.boundary.scatter(r_in, rec, attenuation, scattered)
+    }
+
+    fn interface(&self) -> Option<Interface> {
+        Some(self.interface)
+    }
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use crate::hittable::HitRecord;
+use crate::texture::{Texture, solid_color::SolidColor};
+use crate::utility::{color::Color, spectrum, PI};
+
+pub enum FilmBase {
+    // What lies beneath the film.
+    Dielectric(f64), // Index of refraction
+    Conductor(Color), // Reflectance of the bare metal
+}
+
+pub struct ThinFilm {
+    ir: f64, // Index of Refraction of the film
+    thickness: Arc<dyn Texture>, // Red channel, times scale, gives the thickness in nanometers
+    scale: f64,
+}
+
+impl ThinFilm {
+    // Wavelengths used to stand in for the red, green and blue channels outside spectral mode.
+    const RGB_WAVELENGTHS: [f64; 3] = [630.0, 532.0, 465.0];
+
+    pub fn new(index_of_refraction: f64, thickness: f64) -> Self {
+        ThinFilm {
+            ir: index_of_refraction,
+            thickness: Arc::new(SolidColor::from_value(1.0)),
+            scale: thickness,
+        }
+    }
+
+    pub fn from_texture(index_of_refraction: f64, thickness: Arc<dyn Texture>, max_thickness: f64) -> Self {
+        ThinFilm { ir: index_of_refraction, thickness, scale: max_thickness }
+    }
+
+    pub fn reflectance(&self, rec: &HitRecord, cos_theta: f64, n_incident: f64, base: &FilmBase) -> Color {
+        // Reflectance of the coated interface, per channel in RGB mode or for the hero wavelength
+        // in spectral mode.
+        let thickness = self.scale * self.thickness.value_at(rec).x.max(0.0);
+        let metal = |channel: usize| match base {
+            FilmBase::Conductor(c) => c[channel],
+            FilmBase::Dielectric(_) => 0.0,
+        };
+
+        match rec.wavelength {
+            Some(lambda) => {
+                // The metal's own color, as the spectrum it stands for, at this wavelength.
+                let metal = match base {
+                    FilmBase::Conductor(c) => spectrum::rgb_to_spectrum(*c, lambda),
+                    FilmBase::Dielectric(_) => 0.0,
+                };
+                let r = self.reflectance_at(cos_theta, n_incident, base, metal, thickness, lambda);
+                Color::new(r, r, r)
+            },
+            None => {
+                let [r, g, b] = ThinFilm::RGB_WAVELENGTHS;
+                Color::new(
+                    self.reflectance_at(cos_theta, n_incident, base, metal(0), thickness, r),
+                    self.reflectance_at(cos_theta, n_incident, base, metal(1), thickness, g),
+                    self.reflectance_at(cos_theta, n_incident, base, metal(2), thickness, b),
+                )
+            },
+        }
+    }
+
+    fn reflectance_at(&self, cos_theta: f64, n_incident: f64, base: &FilmBase, metal: f64, thickness: f64, lambda: f64) -> f64 {
+        // Airy summation of the light bouncing between the two sides of the film, averaged over
+        // both polarizations.
+        let sin2_theta = 1.0 - cos_theta * cos_theta;
+        let sin2_film = (n_incident / self.ir).powi(2) * sin2_theta;
+        if sin2_film >= 1.0 {return 1.0}
+        let cos_film = (1.0 - sin2_film).sqrt();
+
+        let (top_s, top_p) = amplitudes(n_incident, self.ir, cos_theta, cos_film);
+        let (bottom_s, bottom_p) = match base {
+            FilmBase::Dielectric(n) => {
+                let sin2_base = (n_incident / n).powi(2) * sin2_theta;
+                if sin2_base >= 1.0 {return 1.0} // Nothing is transmitted, so everything reflects.
+                amplitudes(self.ir, *n, cos_film, (1.0 - sin2_base).sqrt())
+            },
+            // Approximate a metal by a real amplitude with the phase flip of a dense medium.
+            FilmBase::Conductor(_) => (-metal.sqrt(), -metal.sqrt()),
+        };
+
+        let phase = 4.0 * PI * self.ir * thickness * cos_film / lambda;
+        0.5 * (airy(top_s, bottom_s, phase) + airy(top_p, bottom_p, phase))
+    }
+}
+
+fn amplitudes(n_i: f64, n_t: f64, cos_i: f64, cos_t: f64) -> (f64, f64) {
+    // Fresnel amplitude coefficients for s and p polarized light.
+    let s = (n_i * cos_i - n_t * cos_t) / (n_i * cos_i + n_t * cos_t);
+    let p = (n_t * cos_i - n_i * cos_t) / (n_t * cos_i + n_i * cos_t);
+    (s, p)
+}
+
+fn airy(r12: f64, r23: f64, phase: f64) -> f64 {
+    let interference = 2.0 * r12 * r23 * phase.cos();
+    (r12 * r12 + r23 * r23 + interference) / (1.0 + r12 * r12 * r23 * r23 + interference)
+}
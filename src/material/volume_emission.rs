@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use crate::{hittable::HitRecord, texture::Texture, utility::{color::Color, ray::Ray}};
+
+use super::{Mat, Material};
+
+pub struct VolumeEmission {
+    // Phase function of a glowing medium. The media sample collisions in proportion to their
+    // density, so emitting emission / density at every collision accumulates the emission
+    // coefficient along the ray.
+    phase_function: Mat,
+    emission: Arc<dyn Texture>, // Radiance emitted per unit length
+    density: Arc<dyn Texture>,
+    density_scale: f64,
+}
+
+impl VolumeEmission {
+    pub fn new(phase_function: Mat, emission: Arc<dyn Texture>, density: Arc<dyn Texture>, density_scale: f64) -> Self {
+        VolumeEmission{phase_function, emission, density, density_scale}
+    }
+}
+
+impl Material for VolumeEmission {
+    fn scatter(&self, r_in:&Ray, rec:&HitRecord, attenuation: &mut Color, scattered:&mut Ray) -> bool {
+        self.phase_function.scatter(r_in, rec, attenuation, scattered)
+    }
+
+    fn scatter_sampled(&self, r_in:&Ray, rec:&HitRecord, attenuation: &mut Color, scattered:&mut Ray) -> Option<f64> {
+        self.phase_function.scatter_sampled(r_in, rec, attenuation, scattered)
+    }
+
+    fn eval(&self, r_in:&Ray, rec:&HitRecord, scattered:&Ray) -> Color {
+        self.phase_function.eval(r_in, rec, scattered)
+    }
+
+    fn scattering_pdf(&self, r_in:&Ray, rec:&HitRecord, scattered:&Ray) -> f64 {
+        self.phase_function.scattering_pdf(r_in, rec, scattered)
+    }
+
+    fn emitted(&self, _r_in:&Ray, rec:&HitRecord) -> Color {
+        // Collisions never land where there is no density, so neither can emission.
+        let density = self.density_scale * self.density.value_at(rec).x.clamp(0.0, 1.0);
+        if density <= 0.0 {return Color::default()}
+        self.emission.value_at(rec) / density
+    }
+}
@@ -1,11 +1,26 @@
+use crate::hittable::HitRecord;
 use crate::utility::{vec3::Vec3, color::Color};
 
 pub mod solid_color;
 pub mod checkered;
 pub mod image;
 pub mod noise;
+pub mod voxel_grid;
+pub mod blackbody;
+pub mod uv_transform;
+pub mod mapping;
+pub mod color_ramp;
+pub mod procedural;
+pub mod node;
+pub mod graph;
 pub trait Texture: Sync + Send {
     fn value(&self, u:f64, v:f64, p:Vec3) -> Color;
+
+    fn value_at(&self, rec:&HitRecord) -> Color {
+        // The texture at a hit point. Textures that can filter over the patch of surface a ray
+        // covers (rec.differentials) override this.
+        self.value(rec.u, rec.v, rec.p)
+    }
 }
 
 
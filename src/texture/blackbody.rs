@@ -0,0 +1,43 @@
+use std::sync::Arc;
+
+use super::Texture;
+use crate::hittable::HitRecord;
+use crate::utility::{vec3::Vec3, color::Color, spectrum};
+
+const TABLE_SIZE: usize = 256;
+
+pub struct Blackbody {
+    temperature: Arc<dyn Texture>, // Red channel in [0,1], mapped onto the temperature range
+    colors: Vec<Color>, // blackbody_color is too slow to evaluate per lookup, so tabulate it
+}
+
+impl Blackbody {
+    pub fn new(temperature: Arc<dyn Texture>, min_kelvin: f64, max_kelvin: f64, intensity: f64) -> Self {
+        // Intensity is the luminance at max_kelvin. Cooler points are dimmer by the
+        // Stefan-Boltzmann law, so a flame's cool edges glow a dull red.
+        assert!(max_kelvin > 0.0, "The hottest temperature must be above absolute zero");
+        let colors = (0..TABLE_SIZE).map(|i| {
+            let kelvin = min_kelvin + (max_kelvin - min_kelvin) * i as f64 / (TABLE_SIZE - 1) as f64;
+            spectrum::blackbody_color(kelvin) * (intensity * (kelvin / max_kelvin).powi(4))
+        }).collect();
+
+        Blackbody{temperature, colors}
+    }
+
+    fn color(&self, temperature: f64) -> Color {
+        let t = temperature.clamp(0.0, 1.0) * (TABLE_SIZE - 1) as f64;
+        let i = (t as usize).min(TABLE_SIZE - 2);
+        let f = t - i as f64;
+        self.colors[i] * (1.0 - f) + self.colors[i + 1] * f
+    }
+}
+
+impl Texture for Blackbody {
+    fn value(&self, u:f64, v:f64, p:Vec3) -> Color {
+        self.color(self.temperature.value(u, v, p).x)
+    }
+
+    fn value_at(&self, rec:&HitRecord) -> Color {
+        self.color(self.temperature.value_at(rec).x)
+    }
+}
@@ -1,3 +1,4 @@
+use crate::hittable::HitRecord;
 use crate::utility::{color::Color, vec3::Vec3};
 use super::{Texture, solid_color::SolidColor};
 use std::sync::Arc;
@@ -36,4 +37,74 @@ impl Texture for Checkered {
 
         if is_even {self.even.value(u, v, p)} else {self.odd.value(u, v, p)}
     }
-}
\ No newline at end of file
+
+    fn value_at(&self, rec:&HitRecord) -> Color {
+        // Box filter the pattern over the ray footprint, so distant checks fade to their
+        // average instead of aliasing.
+        let Some(d) = rec.differentials else {return self.value(rec.u, rec.v, rec.p)};
+
+        let wave = |a: usize| filtered_square_wave(
+            self.inv_scale * rec.p[a],
+            self.inv_scale * d.dpdx[a].abs().max(d.dpdy[a].abs()),
+        );
+
+        // The pattern is even where the product of the three waves is +1.
+        let even_weight = 0.5 + 0.5 * wave(0) * wave(1) * wave(2);
+        let even = if even_weight > 0.0 {self.even.value_at(rec)} else {Color::default()};
+        let odd = if even_weight < 1.0 {self.odd.value_at(rec)} else {Color::default()};
+        even * even_weight + odd * (1.0 - even_weight)
+    }
+}
+
+pub struct UvCheckered {
+    // A checkerboard laid out in texture coordinates rather than space, so it follows the
+    // surface: checks.0 columns across u and checks.1 rows across v.
+    checks: (f64, f64),
+    even: Arc<dyn Texture>,
+    odd: Arc<dyn Texture>,
+}
+
+impl UvCheckered {
+    pub fn from_colors(checks: (f64, f64), c1: Color, c2: Color) -> Self {
+        UvCheckered {
+            checks,
+            even: Arc::new(SolidColor::new(c1)),
+            odd: Arc::new(SolidColor::new(c2)),
+        }
+    }
+}
+
+impl Texture for UvCheckered {
+    fn value(&self, u:f64, v:f64, p:Vec3) -> Color {
+        let u_integer = (self.checks.0 * u).floor() as i64;
+        let v_integer = (self.checks.1 * v).floor() as i64;
+
+        if (u_integer + v_integer).rem_euclid(2) == 0 {self.even.value(u, v, p)} else {self.odd.value(u, v, p)}
+    }
+
+    fn value_at(&self, rec:&HitRecord) -> Color {
+        // Filtered like Checkered, over the footprint in texture coordinates.
+        let Some(d) = rec.differentials else {return self.value(rec.u, rec.v, rec.p)};
+
+        let u_wave = filtered_square_wave(self.checks.0 * rec.u, self.checks.0 * d.duvdx.0.abs().max(d.duvdy.0.abs()));
+        let v_wave = filtered_square_wave(self.checks.1 * rec.v, self.checks.1 * d.duvdx.1.abs().max(d.duvdy.1.abs()));
+
+        let even_weight = 0.5 + 0.5 * u_wave * v_wave;
+        let even = if even_weight > 0.0 {self.even.value_at(rec)} else {Color::default()};
+        let odd = if even_weight < 1.0 {self.odd.value_at(rec)} else {Color::default()};
+        even * even_weight + odd * (1.0 - even_weight)
+    }
+}
+
+fn filtered_square_wave(x: f64, w: f64) -> f64 {
+    // The average over [x - w/2, x + w/2] of a square wave that is +1 on even unit intervals
+    // and -1 on odd ones. Its integral is a triangle wave.
+    if w < 1e-8 {
+        return if (x.floor() as i64).rem_euclid(2) == 0 {1.0} else {-1.0}
+    }
+    let triangle = |x: f64| {
+        let f = x.rem_euclid(2.0);
+        if f < 1.0 {f} else {2.0 - f}
+    };
+    (triangle(x + w / 2.0) - triangle(x - w / 2.0)) / w
+}
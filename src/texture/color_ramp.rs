@@ -0,0 +1,27 @@
+use crate::utility::color::Color;
+
+#[derive(Clone)]
+pub struct ColorRamp {
+    // A gradient through colors placed at positions along [0,1], for turning a scalar pattern
+    // into colors. Outside the first and last stops the end colors carry on.
+    stops: Vec<(f64, Color)>,
+}
+
+impl ColorRamp {
+    pub fn new(mut stops: Vec<(f64, Color)>) -> Self {
+        assert!(!stops.is_empty(), "A color ramp needs at least one stop");
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        ColorRamp { stops }
+    }
+
+    pub fn eval(&self, t: f64) -> Color {
+        let i = self.stops.partition_point(|&(position, _)| position <= t);
+        if i == 0 {return self.stops[0].1}
+        if i == self.stops.len() {return self.stops[i - 1].1}
+
+        let (t0, c0) = self.stops[i - 1];
+        let (t1, c1) = self.stops[i];
+        let f = (t - t0) / (t1 - t0);
+        c0 * (1.0 - f) + c1 * f
+    }
+}
@@ -0,0 +1,224 @@
+use std::{collections::HashMap, fs, path::Path, sync::Arc};
+
+use crate::utility::color::Color;
+use super::{
+    Texture,
+    checkered::{Checkered, UvCheckered},
+    color_ramp::ColorRamp,
+    image::{ColorSpace, Filter, ImageTexture, Wrap},
+    node::{Clamp, HsvAdjust, Invert, Lerp, Math, MathOp, Ramp},
+    noise::NoiseTexture,
+    procedural::{CellFeature, Cellular, DomainWarp, Fbm, Marble, Ridged, Wood},
+    solid_color::SolidColor,
+};
+
+pub struct TextureGraph {
+    // A network of textures described in a text file, one node per line:
+    //
+    //     # Comments start with a hash.
+    //     grain = fbm 3
+    //     rings = wood 8 0.6 0:0.75,0.52,0.3 1:0.35,0.18,0.08
+    //     worn = lerp rings 0.5,0.45,0.4 grain
+    //
+    // Texture inputs are the name of an earlier node, a number for a gray constant, or r,g,b
+    // for a color. Names may not start like a number, so the two never clash. Ramps take
+    // stops as position:r,g,b. The last node is the output, and any node can be looked up by
+    // name to drive a different material input.
+    nodes: HashMap<String, Arc<dyn Texture>>,
+    output: Arc<dyn Texture>,
+}
+
+impl TextureGraph {
+    pub fn new(filename: &str) -> Self {
+        match TextureGraph::load(filename) {
+            Ok(graph) => graph,
+            Err(e) => panic!("Could not read {filename} into texture graph: {e}")
+        }
+    }
+
+    pub fn load(filename: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(filename).map_err(|e| e.to_string())?;
+        TextureGraph::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut nodes = HashMap::new();
+        let mut output = None;
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {continue}
+
+            let (name, definition) = line.split_once('=').ok_or(format!("line {}: expected 'name = node'", number + 1))?;
+            let name = name.trim();
+            if name.is_empty() || name.contains(char::is_whitespace) || is_constant(name) {
+                return Err(format!("line {}: bad node name '{name}'", number + 1))
+            }
+
+            let node = TextureGraph::node_from(&nodes, definition).map_err(|e| format!("line {}: {e}", number + 1))?;
+            nodes.insert(name.to_string(), node.clone());
+            output = Some(node);
+        }
+
+        let output = output.ok_or("no nodes")?;
+        Ok(TextureGraph { nodes, output })
+    }
+
+    pub fn output(&self) -> Arc<dyn Texture> {
+        self.output.clone()
+    }
+
+    pub fn node(&self, name: &str) -> Option<Arc<dyn Texture>> {
+        self.nodes.get(name).cloned()
+    }
+
+    fn node_from(nodes: &HashMap<String, Arc<dyn Texture>>, definition: &str) -> Result<Arc<dyn Texture>, String> {
+        let mut words = definition.split_whitespace();
+        let kind = words.next().ok_or("missing node type")?;
+        let args: Vec<&str> = words.collect();
+
+        let arg = |i: usize| args.get(i).copied().ok_or(format!("'{kind}' is missing argument {}", i + 1));
+        let number = |i: usize| arg(i).and_then(parse_number);
+        let color = |i: usize| arg(i).and_then(parse_color);
+        let input = |i: usize| arg(i).and_then(|a| parse_input(nodes, a));
+        let ramp = |from: usize| parse_ramp(args.get(from..).unwrap_or(&[]));
+        let math = |op: MathOp| -> Result<Arc<dyn Texture>, String> {Ok(Arc::new(Math::new(op, input(0)?, input(1)?)))};
+
+        let node: Arc<dyn Texture> = match kind {
+            "constant" => input(0)?,
+            "image" => {
+                let filename = arg(0)?;
+                if !Path::new(filename).exists() {return Err(format!("no image file '{filename}'"))}
+                let texture = match args.get(1).copied() {
+                    Some("linear") => ImageTexture::with_color_space(filename, ColorSpace::Linear),
+                    Some(other) => return Err(format!("unknown color space '{other}'")),
+                    None => ImageTexture::new(filename),
+                };
+                Arc::new(texture.with_filter(Filter::Trilinear).with_wrap(Wrap::Repeat))
+            }
+            "checker" => Arc::new(Checkered::from_colors(number(0)?, color(1)?, color(2)?)),
+            "uv_checker" => Arc::new(UvCheckered::from_colors((number(0)?, number(1)?), color(2)?, color(3)?)),
+            "noise" => Arc::new(NoiseTexture::new(number(0)?)),
+            "fbm" => Arc::new(Fbm::new(number(0)?)),
+            "ridged" => Arc::new(Ridged::new(number(0)?)),
+            "cellular" => {
+                let feature = match args.get(1).copied() {
+                    None | Some("nearest") => CellFeature::Nearest,
+                    Some("second") => CellFeature::SecondNearest,
+                    Some("edges") => CellFeature::Edges,
+                    Some(other) => return Err(format!("unknown cell feature '{other}'")),
+                };
+                Arc::new(Cellular::new(number(0)?, feature))
+            }
+            "wood" => Arc::new(Wood::new(number(0)?, number(1)?, ramp(2)?)),
+            "marble" => Arc::new(Marble::new(number(0)?, number(1)?, ramp(2)?)),
+            "warp" => Arc::new(DomainWarp::new(input(0)?, number(1)?, number(2)?)),
+            "add" => math(MathOp::Add)?,
+            "subtract" => math(MathOp::Subtract)?,
+            "multiply" => math(MathOp::Multiply)?,
+            "divide" => math(MathOp::Divide)?,
+            "min" => math(MathOp::Min)?,
+            "max" => math(MathOp::Max)?,
+            "lerp" => Arc::new(Lerp::new(input(0)?, input(1)?, input(2)?)),
+            "ramp" => Arc::new(Ramp::new(input(0)?, ramp(1)?)),
+            "hsv" => Arc::new(HsvAdjust::new(input(0)?, number(1)?, number(2)?, number(3)?)),
+            "invert" => Arc::new(Invert::new(input(0)?)),
+            "clamp" => Arc::new(Clamp::new(input(0)?, number(1)?, number(2)?)),
+            _ => return Err(format!("unknown node type '{kind}'")),
+        };
+        Ok(node)
+    }
+}
+
+fn parse_number(word: &str) -> Result<f64, String> {
+    word.parse::<f64>().map_err(|_| format!("bad number '{word}'"))
+}
+
+fn parse_color(word: &str) -> Result<Color, String> {
+    let channels = word.split(',').map(parse_number).collect::<Result<Vec<_>, _>>()?;
+    match channels[..] {
+        [r, g, b] => Ok(Color::new(r, g, b)),
+        [v] => Ok(Color::new(v, v, v)),
+        _ => Err(format!("bad color '{word}'")),
+    }
+}
+
+fn parse_input(nodes: &HashMap<String, Arc<dyn Texture>>, word: &str) -> Result<Arc<dyn Texture>, String> {
+    if let Some(node) = nodes.get(word) {
+        return Ok(node.clone())
+    }
+    if is_constant(word) {
+        return Ok(Arc::new(SolidColor::new(parse_color(word)?)))
+    }
+    Err(format!("no node named '{word}'"))
+}
+
+fn is_constant(word: &str) -> bool {
+    // Whether an input is written as a number or color rather than a node name.
+    word.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+' || c == '.')
+}
+
+fn parse_ramp(words: &[&str]) -> Result<ColorRamp, String> {
+    if words.is_empty() {return Err("a ramp needs at least one stop".to_string())}
+    let stops = words.iter().map(|word| {
+        let (position, color) = word.split_once(':').ok_or(format!("bad ramp stop '{word}'"))?;
+        Ok((parse_number(position)?, parse_color(color)?))
+    }).collect::<Result<Vec<_>, String>>()?;
+    Ok(ColorRamp::new(stops))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utility::vec3::Vec3;
+
+    fn error(text: &str) -> String {
+        match TextureGraph::parse(text) {
+            Ok(_) => panic!("expected '{text}' to be rejected"),
+            Err(e) => e,
+        }
+    }
+
+    fn gray(texture: Arc<dyn Texture>) -> f64 {
+        texture.value(0.5, 0.5, Vec3::default()).x
+    }
+
+    #[test]
+    fn bad_lines() {
+        assert_eq!(error("a = constant 1\nno equals sign"), "line 2: expected 'name = node'");
+        assert_eq!(error("a ="), "line 1: missing node type");
+        assert_eq!(error("= constant 1"), "line 1: bad node name ''");
+        assert_eq!(error("two words = constant 1"), "line 1: bad node name 'two words'");
+        assert_eq!(error("a = clamp 0.5 0"), "line 1: 'clamp' is missing argument 3");
+        assert_eq!(error("a = fbm x"), "line 1: bad number 'x'");
+        assert_eq!(error("a = constant 1,2"), "line 1: bad color '1,2'");
+        assert_eq!(error("# only a comment\n\n"), "no nodes");
+    }
+
+    #[test]
+    fn unknown_nodes() {
+        assert_eq!(error("a = sparkle 3"), "line 1: unknown node type 'sparkle'");
+        assert_eq!(error("a = cellular 4 round"), "line 1: unknown cell feature 'round'");
+    }
+
+    #[test]
+    fn forward_references() {
+        assert_eq!(error("a = invert b\nb = constant 1"), "line 1: no node named 'b'");
+        assert_eq!(error("a = add a 1"), "line 1: no node named 'a'");
+    }
+
+    #[test]
+    fn names_and_numbers() {
+        for name in ["1", "2x", "-a", "+1", ".5"] {
+            assert_eq!(error(&format!("{name} = constant 1")), format!("line 1: bad node name '{name}'"));
+        }
+
+        // Inputs starting like a number are constants, anything else must name a node.
+        let text = "half = constant 0.5\nsum = add half 0.25\nscaled = multiply sum 2";
+        let graph = TextureGraph::parse(text).unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!(gray(graph.node("half").unwrap()), 0.5);
+        assert_eq!(gray(graph.node("sum").unwrap()), 0.75);
+        assert_eq!(gray(graph.output()), 1.5);
+        assert_eq!(error("a = add 0.5 e1"), "line 1: no node named 'e1'");
+    }
+}
@@ -1,42 +1,181 @@
 mod rtw_image;
 
-use crate::utility::{vec3::Vec3,color::Color,interval::Interval};
-use super::Texture;
+use crate::hittable::HitRecord;
+use crate::utility::{vec3::Vec3,color::Color};
+use super::{Texture, uv_transform::UvTransform};
 use rtw_image::RTWImage;
 
+pub use rtw_image::ColorSpace;
+
+// How texels are combined between texel centers.
+#[derive(Clone, Copy)]
+#[allow(unused)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+    Bicubic, // Catmull-Rom, over the 4x4 nearest texels
+    Trilinear, // Bilinear between the two MIP levels that best match the ray footprint
+}
+
+// What lookups outside [0,1] see.
+#[derive(Clone, Copy)]
+#[allow(unused)]
+pub enum Wrap {
+    Repeat,
+    Mirror,
+    Clamp,
+    Border(Color),
+}
 
 pub struct ImageTexture {
     image: RTWImage,
+    filter: Filter,
+    wrap: Wrap,
+    transform: UvTransform,
 }
 
 impl ImageTexture {
     pub fn new(filename: &str) -> Self{
+        ImageTexture::load(filename, None)
+    }
+
+    pub fn with_color_space(filename: &str, color_space: ColorSpace) -> Self {
+        // Use ColorSpace::Linear for data maps stored in 8 or 16 bit files.
+        ImageTexture::load(filename, Some(color_space))
+    }
+
+    fn load(filename: &str, color_space: Option<ColorSpace>) -> Self {
         ImageTexture {
-            image: RTWImage::new(filename)
+            image: RTWImage::new(filename, color_space),
+            filter: Filter::Nearest,
+            wrap: Wrap::Clamp,
+            transform: UvTransform::default(),
+        }
+    }
+
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn with_wrap(mut self, wrap: Wrap) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn with_transform(mut self, transform: UvTransform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    fn wrap_index(&self, i: i64, n: usize) -> Option<usize> {
+        let n = n as i64;
+        let i = match self.wrap {
+            Wrap::Repeat => i.rem_euclid(n),
+            Wrap::Mirror => {
+                let m = i.rem_euclid(2 * n);
+                if m < n {m} else {2 * n - 1 - m}
+            }
+            Wrap::Clamp => i.clamp(0, n - 1),
+            Wrap::Border(_) => {
+                if i < 0 || i >= n {return None}
+                i
+            }
+        };
+        Some(i as usize)
+    }
+
+    fn texel(&self, level: usize, i: i64, j: i64) -> Color {
+        let width = self.image.level_width(level);
+        let height = self.image.level_height(level);
+        let (Some(i), Some(j)) = (self.wrap_index(i, width), self.wrap_index(j, height)) else {
+            return match self.wrap {
+                Wrap::Border(c) => c,
+                _ => Color::default(),
+            }
+        };
+
+        self.image.pixel_data(level, i, j)
+    }
+
+    fn bilinear(&self, level: usize, x: f64, y: f64) -> Color {
+        // x and y are in [0,1] across the image, with y pointing down.
+        let x = x * self.image.level_width(level) as f64 - 0.5;
+        let y = y * self.image.level_height(level) as f64 - 0.5;
+        let (i, j) = (x.floor() as i64, y.floor() as i64);
+        let (fx, fy) = (x - x.floor(), y - y.floor());
+        let top = self.texel(level, i, j) * (1.0 - fx) + self.texel(level, i + 1, j) * fx;
+        let bottom = self.texel(level, i, j + 1) * (1.0 - fx) + self.texel(level, i + 1, j + 1) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+
+    fn lookup(&self, u: f64, v: f64, footprint: f64) -> Color {
+        // footprint is the width, in texels of the full size image, that the lookup covers.
+        // If we have no texture data, then return solid cyan as a debugging aid.
+        if self.image.height() == 0 {return Color::new(0.0, 1.1, 1.1)}
+
+        let (u, v) = self.transform.apply(u, v);
+        let v = 1.0 - v; // Flip V to image coordinates
+
+        // Continuous texel coordinates, with texel centers at half-integers.
+        let x = u * self.image.width() as f64;
+        let y = v * self.image.height() as f64;
+
+        match self.filter {
+            Filter::Nearest => self.texel(0, x.floor() as i64, y.floor() as i64),
+            Filter::Bilinear => self.bilinear(0, u, v),
+            Filter::Bicubic => {
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (i, j) = (x.floor() as i64, y.floor() as i64);
+                let wx = catmull_rom(x - x.floor());
+                let wy = catmull_rom(y - y.floor());
+
+                let mut c = Color::default();
+                for (dj, wy) in wy.iter().enumerate() {
+                    for (di, wx) in wx.iter().enumerate() {
+                        c = c + self.texel(0, i + di as i64 - 1, j + dj as i64 - 1) * (wx * wy);
+                    }
+                }
+                // The negative lobes can overshoot below zero next to sharp edges.
+                Color::new(c.x.max(0.0), c.y.max(0.0), c.z.max(0.0))
+            }
+            Filter::Trilinear => {
+                let lod = footprint.max(1.0).log2().min((self.image.level_count() - 1) as f64);
+                let level = lod.floor() as usize;
+                let f = lod - level as f64;
+                if f <= 0.0 {return self.bilinear(level, u, v)}
+                self.bilinear(level, u, v) * (1.0 - f) + self.bilinear(level + 1, u, v) * f
+            }
         }
     }
 }
 
 impl Texture for ImageTexture {
     fn value(&self, u:f64, v:f64, _p:Vec3) -> Color {
-        // If we have no texture data, then return solid cyan as a debugging aid.
-        if self.image.height() == 0 {return Color::new(0.0, 1.1, 1.1)}
+        self.lookup(u, v, 0.0)
+    }
 
-        // Clamp input texture coordinates to [0,1] x [1,0]
-        
-        let i = Interval::new(0.0, 1.0);
-        let u = i.clamp(u);
-        let v = 1.0 - i.clamp(v); // Flip V to image coordinates
-
-        let i = (u * self.image.width() as f64) as usize;
-        let j = (v * self.image.height() as f64) as usize;
-        let pixel = self.image.pixel_data(i, j);
-        
-        let color_scale = 1.0 / 255.0;
-        Color::new(
-            color_scale * pixel.0 as f64,
-            color_scale * pixel.1 as f64, 
-            color_scale * pixel.2 as f64
-        )
-    }
-}
\ No newline at end of file
+    fn value_at(&self, rec:&HitRecord) -> Color {
+        // The footprint is the longer of the two pixel-to-pixel steps across the image.
+        let footprint = rec.differentials.map_or(0.0, |d| {
+            let texels = |(du, dv): (f64, f64)| {
+                let (du, dv) = self.transform.apply_vector(du, dv);
+                (du * self.image.width() as f64).hypot(dv * self.image.height() as f64)
+            };
+            texels(d.duvdx).max(texels(d.duvdy))
+        });
+        self.lookup(rec.u, rec.v, footprint)
+    }
+}
+
+fn catmull_rom(t: f64) -> [f64; 4] {
+    // Weights of the four texels around a point a fraction t past the second one.
+    let t2 = t * t;
+    let t3 = t2 * t;
+    [
+        0.5 * (-t3 + 2.0 * t2 - t),
+        0.5 * (3.0 * t3 - 5.0 * t2 + 2.0),
+        0.5 * (-3.0 * t3 + 4.0 * t2 + t),
+        0.5 * (t3 - t2),
+    ]
+}
@@ -1,21 +1,65 @@
-use image::io::Reader as ImageReader;
+use image::{io::Reader as ImageReader, ColorType};
 
+use crate::utility::color::Color;
+
+// How the values stored in an image file relate to linear light.
+#[derive(Clone, Copy)]
+pub enum ColorSpace {
+    Srgb, // Colors, stored gamma encoded
+    Linear, // Data such as roughness or normal maps, and float images
+}
 
 #[derive(Default)]
 pub struct RTWImage {
-    data: Option< Vec<u8>>,
-    image_width: usize,
-    image_height: usize,
-    bytes_per_scanline: usize,
+    // The image followed by its MIP pyramid, each level half the size of the one before,
+    // down to a single pixel.
+    levels: Vec<Level>,
+}
+
+struct Level {
+    data: Vec<f32>, // Linear RGB, three floats per pixel
+    width: usize,
+    height: usize,
+}
+
+impl Level {
+    fn pixel(&self, x: usize, y: usize) -> Color {
+        let x = RTWImage::clamp(x, 0, self.width);
+        let y = RTWImage::clamp(y, 0, self.height);
+        let i = (y * self.width + x) * 3; // index of pixel in vector
+        Color::new(self.data[i] as f64, self.data[i+1] as f64, self.data[i+2] as f64)
+    }
+
+    fn downsample(&self) -> Level {
+        // Average each 2x2 block. Where a side is odd, its last texel is folded into the last
+        // block, which then averages 3 texels across, so nothing is lost at the edges.
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let block = |i: usize, size: usize, full: usize| 2*i..if i + 1 == size {full} else {2*i + 2};
+        let mut data = Vec::with_capacity(width * height * 3);
+        for y in 0..height {
+            for x in 0..width {
+                let (xs, ys) = (block(x, width, self.width), block(y, height, self.height));
+                let count = (xs.len() * ys.len()) as f64;
+                let sum = ys.flat_map(|j| xs.clone().map(move |i| (i, j)))
+                    .fold(Color::default(), |sum, (i, j)| sum + self.pixel(i, j));
+                let c = sum / count;
+                data.extend([c.x as f32, c.y as f32, c.z as f32]);
+            }
+        }
+        Level{data, width, height}
+    }
 }
 
 impl RTWImage {
-    pub fn new(image_filename:&str) -> Self {
+    pub fn new(image_filename:&str, color_space:Option<ColorSpace>) -> Self {
         let mut r = RTWImage::default();
-        if r.load(image_filename) {r} else {panic!("Could not read {image_filename} into file")}
+        if r.load(image_filename, color_space) {r} else {panic!("Could not read {image_filename} into file")}
     }
 
-    pub fn load(&mut self, image_filename:&str) -> bool {
+    pub fn load(&mut self, image_filename:&str, color_space:Option<ColorSpace>) -> bool {
+        // Without an explicit color space, 8 and 16 bit images are taken to be sRGB and float
+        // images (HDR, OpenEXR) to be linear, as is conventional.
         let img = match ImageReader::open(image_filename) {
             Ok(t) => match t.decode() {
                 Ok(t) => t,
@@ -24,32 +68,52 @@ impl RTWImage {
             Err(_) => return false
         };
 
-        self.image_height = img.height() as usize;
-        self.image_width = img.width() as usize;
-        self.bytes_per_scanline = self.image_width * 3;
-        self.data = Option::Some(img.into_rgb8().into_vec()); // This consumes the img so we put it last
-        
+        let is_float = matches!(img.color(), ColorType::Rgb32F | ColorType::Rgba32F);
+        let color_space = color_space.unwrap_or(if is_float {ColorSpace::Linear} else {ColorSpace::Srgb});
+
+        let height = img.height() as usize;
+        let width = img.width() as usize;
+
+        // Converting to floats keeps the full precision of 16 bit and float images.
+        let mut data = img.into_rgb32f().into_vec(); // This consumes the img so we put it last
+        if let ColorSpace::Srgb = color_space {
+            data.iter_mut().for_each(|c| *c = srgb_to_linear(*c));
+        }
+
+        // Filtering happens on linear values, so the pyramid is built after decoding.
+        self.levels = vec![Level{data, width, height}];
+        while self.levels.last().is_some_and(|l| l.width > 1 || l.height > 1) {
+            let next = self.levels.last().unwrap().downsample();
+            self.levels.push(next);
+        }
         
         true
     }
 
     pub fn height(&self) -> usize {
-        self.image_height
+        self.level_height(0)
     }
 
     pub fn width(&self) -> usize {
-        self.image_width
+        self.level_width(0)
     }
 
-    pub fn pixel_data(&self, x: usize, y:usize) -> (u8,u8,u8) {
-        match &self.data {
-            None => (255,0,255),
-            Some(v)=> {
-                let x = RTWImage::clamp(x, 0 ,self.image_width);
-                let y = RTWImage::clamp(y, 0, self.image_height);
-                let i = y*self.bytes_per_scanline + x*3; // index of pixel in vector
-                (v[i], v[i+1], v[i+2])
-            }
+    pub fn level_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    pub fn level_width(&self, level: usize) -> usize {
+        self.levels.get(level).map_or(0, |l| l.width)
+    }
+
+    pub fn level_height(&self, level: usize) -> usize {
+        self.levels.get(level).map_or(0, |l| l.height)
+    }
+
+    pub fn pixel_data(&self, level: usize, x: usize, y:usize) -> Color {
+        match self.levels.get(level) {
+            None => Color::new(1.0, 0.0, 1.0),
+            Some(l) => l.pixel(x, y),
         }
     }
 
@@ -60,3 +124,7 @@ impl RTWImage {
         else {high - 1}
     }
 }
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {c / 12.92} else {((c + 0.055) / 1.055).powf(2.4)}
+}
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use crate::hittable::HitRecord;
+use crate::utility::{color::Color, vec3::Vec3};
+use super::{Texture, uv_transform::UvTransform};
+
+#[derive(Clone, Copy)]
+pub enum Mapping {
+    Uv(UvTransform), // The surface texture coordinates, rotated, scaled and offset
+    Object, // The hit point before instance transforms, so solid textures move with the object
+    Triplanar{scale: f64, sharpness: f64}, // Projected along the three object axes, blended by the normal
+}
+
+pub struct MappedTexture {
+    // Feeds another texture different coordinates. Mapped textures can wrap each other, so the
+    // transforms stack, innermost last.
+    texture: Arc<dyn Texture>,
+    mapping: Mapping,
+}
+
+impl MappedTexture {
+    pub fn new(texture: Arc<dyn Texture>, mapping: Mapping) -> Self {
+        MappedTexture { texture, mapping }
+    }
+}
+
+impl Texture for MappedTexture {
+    fn value(&self, u:f64, v:f64, p:Vec3) -> Color {
+        // Without a hit record, object space is taken to be world space.
+        let mut rec = HitRecord::default();
+        (rec.u, rec.v, rec.p, rec.object_p) = (u, v, p, p);
+        self.value_at(&rec)
+    }
+
+    fn value_at(&self, rec:&HitRecord) -> Color {
+        let mut mapped = rec.clone();
+        match self.mapping {
+            Mapping::Uv(transform) => {
+                (mapped.u, mapped.v) = transform.apply(rec.u, rec.v);
+                if let Some(d) = mapped.differentials.as_mut() {
+                    d.duvdx = transform.apply_vector(d.duvdx.0, d.duvdx.1);
+                    d.duvdy = transform.apply_vector(d.duvdy.0, d.duvdy.1);
+                }
+                self.texture.value_at(&mapped)
+            }
+            Mapping::Object => {
+                // The footprint stays in world space, which only matters to filtering of
+                // rotated objects.
+                mapped.p = rec.object_p;
+                self.texture.value_at(&mapped)
+            }
+            Mapping::Triplanar{scale, sharpness} => {
+                // Each axis projects the point onto the plane across it (Geiss 2007). Surfaces
+                // facing mostly along one axis take their texture from that projection, with
+                // sharpness controlling how quickly the blend narrows at the seams.
+                let n = rec.object_normal;
+                let mut weights = [n.x.abs().powf(sharpness), n.y.abs().powf(sharpness), n.z.abs().powf(sharpness)];
+                let total: f64 = weights.iter().sum();
+                weights = if total > 0.0 {weights.map(|w| w / total)} else {[1.0 / 3.0; 3]};
+
+                let mut c = Color::default();
+                for (axis, (i, j)) in [(2, 1), (0, 2), (0, 1)].into_iter().enumerate() {
+                    if weights[axis] <= 0.0 {continue}
+                    (mapped.u, mapped.v) = (rec.object_p[i] * scale, rec.object_p[j] * scale);
+                    if let (Some(d), Some(original)) = (mapped.differentials.as_mut(), rec.differentials) {
+                        d.duvdx = (original.dpdx[i] * scale, original.dpdx[j] * scale);
+                        d.duvdy = (original.dpdy[i] * scale, original.dpdy[j] * scale);
+                    }
+                    c = c + self.texture.value_at(&mapped) * weights[axis];
+                }
+                c
+            }
+        }
+    }
+}
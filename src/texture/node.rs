@@ -0,0 +1,204 @@
+use std::sync::Arc;
+
+use crate::hittable::HitRecord;
+use crate::utility::{color::Color, vec3::Vec3};
+use super::{Texture, color_ramp::ColorRamp};
+
+// Operators for combining textures into a graph. Each node is itself a texture, so any node
+// output can feed a material input or another node. Constants are plain SolidColor textures.
+// Nodes forward value_at to their inputs, which keeps filtered textures filtered.
+
+#[derive(Clone, Copy)]
+pub enum MathOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide, // Zero where the divisor is zero
+    Min,
+    Max,
+}
+
+pub struct Math {
+    op: MathOp,
+    a: Arc<dyn Texture>,
+    b: Arc<dyn Texture>,
+}
+
+impl Math {
+    pub fn new(op: MathOp, a: Arc<dyn Texture>, b: Arc<dyn Texture>) -> Self {
+        Math { op, a, b }
+    }
+
+    fn eval(&self, input: impl Fn(&Arc<dyn Texture>) -> Color) -> Color {
+        let (a, b) = (input(&self.a), input(&self.b));
+        let apply = |x: f64, y: f64| match self.op {
+            MathOp::Add => x + y,
+            MathOp::Subtract => x - y,
+            MathOp::Multiply => x * y,
+            MathOp::Divide => if y == 0.0 {0.0} else {x / y},
+            MathOp::Min => x.min(y),
+            MathOp::Max => x.max(y),
+        };
+        Color::new(apply(a.x, b.x), apply(a.y, b.y), apply(a.z, b.z))
+    }
+}
+
+impl Texture for Math {
+    fn value(&self, u:f64, v:f64, p:Vec3) -> Color {
+        self.eval(|t| t.value(u, v, p))
+    }
+
+    fn value_at(&self, rec:&HitRecord) -> Color {
+        self.eval(|t| t.value_at(rec))
+    }
+}
+
+pub struct Lerp {
+    // a where t (red channel) is 0, b where it is 1.
+    a: Arc<dyn Texture>,
+    b: Arc<dyn Texture>,
+    t: Arc<dyn Texture>,
+}
+
+impl Lerp {
+    pub fn new(a: Arc<dyn Texture>, b: Arc<dyn Texture>, t: Arc<dyn Texture>) -> Self {
+        Lerp { a, b, t }
+    }
+
+    fn eval(&self, input: impl Fn(&Arc<dyn Texture>) -> Color) -> Color {
+        let t = input(&self.t).x;
+        input(&self.a) * (1.0 - t) + input(&self.b) * t
+    }
+}
+
+impl Texture for Lerp {
+    fn value(&self, u:f64, v:f64, p:Vec3) -> Color {
+        self.eval(|t| t.value(u, v, p))
+    }
+
+    fn value_at(&self, rec:&HitRecord) -> Color {
+        self.eval(|t| t.value_at(rec))
+    }
+}
+
+pub struct Ramp {
+    // Colors a scalar input (red channel) with a color ramp.
+    input: Arc<dyn Texture>,
+    ramp: ColorRamp,
+}
+
+impl Ramp {
+    pub fn new(input: Arc<dyn Texture>, ramp: ColorRamp) -> Self {
+        Ramp { input, ramp }
+    }
+}
+
+impl Texture for Ramp {
+    fn value(&self, u:f64, v:f64, p:Vec3) -> Color {
+        self.ramp.eval(self.input.value(u, v, p).x)
+    }
+
+    fn value_at(&self, rec:&HitRecord) -> Color {
+        self.ramp.eval(self.input.value_at(rec).x)
+    }
+}
+
+pub struct HsvAdjust {
+    input: Arc<dyn Texture>,
+    hue_shift: f64, // In turns, so 0.5 swaps every hue for its complement
+    saturation: f64, // Scale factors
+    value: f64,
+}
+
+impl HsvAdjust {
+    pub fn new(input: Arc<dyn Texture>, hue_shift: f64, saturation: f64, value: f64) -> Self {
+        HsvAdjust { input, hue_shift, saturation, value }
+    }
+
+    fn adjust(&self, c: Color) -> Color {
+        let (h, s, v) = rgb_to_hsv(c);
+        hsv_to_rgb((h + self.hue_shift).rem_euclid(1.0), (s * self.saturation).clamp(0.0, 1.0), v * self.value)
+    }
+}
+
+impl Texture for HsvAdjust {
+    fn value(&self, u:f64, v:f64, p:Vec3) -> Color {
+        self.adjust(self.input.value(u, v, p))
+    }
+
+    fn value_at(&self, rec:&HitRecord) -> Color {
+        self.adjust(self.input.value_at(rec))
+    }
+}
+
+pub struct Invert {
+    // One minus the input, for colors and scalars in [0,1].
+    input: Arc<dyn Texture>,
+}
+
+impl Invert {
+    pub fn new(input: Arc<dyn Texture>) -> Self {
+        Invert { input }
+    }
+}
+
+impl Texture for Invert {
+    fn value(&self, u:f64, v:f64, p:Vec3) -> Color {
+        Color::new(1.0, 1.0, 1.0) - self.input.value(u, v, p)
+    }
+
+    fn value_at(&self, rec:&HitRecord) -> Color {
+        Color::new(1.0, 1.0, 1.0) - self.input.value_at(rec)
+    }
+}
+
+pub struct Clamp {
+    input: Arc<dyn Texture>,
+    min: f64,
+    max: f64,
+}
+
+impl Clamp {
+    pub fn new(input: Arc<dyn Texture>, min: f64, max: f64) -> Self {
+        Clamp { input, min, max }
+    }
+
+    fn clamp(&self, c: Color) -> Color {
+        Color::new(c.x.clamp(self.min, self.max), c.y.clamp(self.min, self.max), c.z.clamp(self.min, self.max))
+    }
+}
+
+impl Texture for Clamp {
+    fn value(&self, u:f64, v:f64, p:Vec3) -> Color {
+        self.clamp(self.input.value(u, v, p))
+    }
+
+    fn value_at(&self, rec:&HitRecord) -> Color {
+        self.clamp(self.input.value_at(rec))
+    }
+}
+
+fn rgb_to_hsv(c: Color) -> (f64, f64, f64) {
+    // Hue in turns [0,1), saturation in [0,1], and value, the largest component.
+    let max = c.x.max(c.y).max(c.z);
+    let min = c.x.min(c.y).min(c.z);
+    let delta = max - min;
+    if delta <= 0.0 {return (0.0, 0.0, max)}
+
+    let hue = if max == c.x {
+        (c.y - c.z) / delta
+    } else if max == c.y {
+        2.0 + (c.z - c.x) / delta
+    } else {
+        4.0 + (c.x - c.y) / delta
+    };
+    ((hue / 6.0).rem_euclid(1.0), delta / max, max)
+}
+
+fn hsv_to_rgb(h: f64, s: f64, v: f64) -> Color {
+    let channel = |n: f64| {
+        let k = (n + h * 6.0).rem_euclid(6.0);
+        v - v * s * k.min(4.0 - k).clamp(0.0, 1.0)
+    };
+    Color::new(channel(5.0), channel(3.0), channel(1.0))
+}
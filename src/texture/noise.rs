@@ -1,4 +1,5 @@
 pub mod perlin;
+pub mod worley;
 use super::Texture;
 use perlin::Perlin;
 use crate::utility::{vec3::Vec3, color::Color};
@@ -12,6 +12,9 @@ pub struct Perlin {
 
 impl Perlin {
     const POINT_COUNT:usize = 256;
+    // Normalized sums of Perlin noise seldom stray past +-2/3, so fbm is stretched by this
+    // much to cover roughly [-1,1].
+    const FBM_STRETCH: f64 = 1.5;
 
     pub fn new() -> Self {
         let mut ranvec = Box::from_iter([Vec3::default();Self::POINT_COUNT]);
@@ -118,6 +121,46 @@ impl Perlin {
 
         accum.abs()
     }
+
+    pub fn fbm(&self, p: Vec3, octaves: i32, lacunarity: f64, gain: f64) -> f64 {
+        // Fractional Brownian motion: octaves of noise, each lacunarity times finer and gain
+        // times fainter than the last. Unlike turb the sum keeps its sign, roughly in [-1,1].
+        let mut accum = 0.0;
+        let mut temp_p = p;
+        let mut weight = 1.0;
+        let mut total_weight = 0.0;
+
+        for _ in 0..octaves {
+            accum += weight*self.noise(temp_p);
+            total_weight += weight;
+            weight *= gain;
+            temp_p = temp_p * lacunarity;
+        }
+
+        if total_weight > 0.0 {accum / total_weight * Perlin::FBM_STRETCH} else {0.0}
+    }
+
+    pub fn ridged(&self, p: Vec3, octaves: i32, lacunarity: f64, gain: f64) -> f64 {
+        // Ridged multifractal (Musgrave 1994): sharp crests where the noise crosses zero, with
+        // each octave weighted by the one before, so fine detail gathers on the ridges and the
+        // valleys stay smooth. In [0,1].
+        let mut accum = 0.0;
+        let mut temp_p = p;
+        let mut weight = 1.0;
+        let mut amplitude = 1.0;
+        let mut total_amplitude = 0.0;
+
+        for _ in 0..octaves {
+            let signal = (1.0 - self.noise(temp_p).abs()).powi(2) * weight;
+            weight = (signal * 2.0).clamp(0.0, 1.0);
+            accum += amplitude * signal;
+            total_amplitude += amplitude;
+            amplitude *= gain;
+            temp_p = temp_p * lacunarity;
+        }
+
+        if total_amplitude > 0.0 {accum / total_amplitude} else {0.0}
+    }
 }
 
 impl Default for Perlin {
@@ -0,0 +1,66 @@
+use crate::utility::{rand, vec3::Vec3};
+
+use super::perlin::Perlin;
+
+pub struct Worley {
+    // Cellular noise (Worley 1996): one feature point scattered in every unit cell of space,
+    // measured by the distance to the nearest ones.
+    jitter: Box<[Vec3]>,
+    perm_x: Box<[usize]>,
+    perm_y: Box<[usize]>,
+    perm_z: Box<[usize]>,
+}
+
+impl Worley {
+    const POINT_COUNT:usize = 256;
+
+    pub fn new() -> Self {
+        let jitter = (0..Self::POINT_COUNT)
+            .map(|_| Vec3::new(rand::random_double(), rand::random_double(), rand::random_double()))
+            .collect();
+
+        Worley {
+            jitter,
+            perm_x: Perlin::perlin_generate_perm(),
+            perm_y: Perlin::perlin_generate_perm(),
+            perm_z: Perlin::perlin_generate_perm(),
+        }
+    }
+
+    fn feature_point(&self, i: i32, j: i32, k: i32) -> Vec3 {
+        let hash = self.perm_x[(i & 255) as usize] ^ self.perm_y[(j & 255) as usize] ^ self.perm_z[(k & 255) as usize];
+        Vec3::new(i as f64, j as f64, k as f64) + self.jitter[hash]
+    }
+
+    pub fn distances(&self, p: Vec3) -> (f64, f64) {
+        // Distances to the nearest and the second nearest feature points. The points are
+        // within their own cells, so the nearest two are almost always among the 27 cells
+        // around p. With one point per cell, the second nearest can occasionally lie further
+        // out, in which case a farther point is reported in its place.
+        let (i, j, k) = (p.x.floor() as i32, p.y.floor() as i32, p.z.floor() as i32);
+        let mut nearest = f64::INFINITY;
+        let mut second = f64::INFINITY;
+
+        for di in -1..=1 {
+            for dj in -1..=1 {
+                for dk in -1..=1 {
+                    let d = (self.feature_point(i + di, j + dj, k + dk) - p).norm2();
+                    if d < nearest {
+                        second = nearest;
+                        nearest = d;
+                    } else if d < second {
+                        second = d;
+                    }
+                }
+            }
+        }
+
+        (nearest.sqrt(), second.sqrt())
+    }
+}
+
+impl Default for Worley {
+    fn default() -> Self {
+        Worley::new()
+    }
+}
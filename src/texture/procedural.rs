@@ -0,0 +1,197 @@
+use std::sync::Arc;
+
+use crate::hittable::HitRecord;
+use crate::utility::{color::Color, vec3::Vec3};
+use super::{Texture, color_ramp::ColorRamp, noise::{perlin::Perlin, worley::Worley}};
+
+// Solid textures built from noise. The scalar patterns come out as grays in [0,1], so they can
+// drive scalar inputs (roughness, masks, heights) directly; wood and marble map theirs through
+// a color ramp. Every pattern reads p, so DomainWarp can distort any of them.
+
+#[derive(Clone, Copy)]
+pub struct Octaves {
+    pub count: i32,
+    pub lacunarity: f64, // How much finer each octave is than the last
+    pub gain: f64, // How much fainter each octave is than the last
+}
+
+impl Default for Octaves {
+    fn default() -> Self {
+        Octaves{count: 6, lacunarity: 2.0, gain: 0.5}
+    }
+}
+
+// Typical ranges of the Worley distances with one feature point per unit cell: the second
+// nearest mostly lies between 0.4 and 1.4, and its gap to the nearest rarely exceeds 0.25.
+// Cellular maps these onto [0,1].
+const SECOND_NEAREST_MIN: f64 = 0.4;
+const EDGE_WIDTH: f64 = 0.25;
+
+fn gray(x: f64) -> Color {
+    let x = x.clamp(0.0, 1.0);
+    Color::new(x, x, x)
+}
+
+pub struct Fbm {
+    noise: Perlin,
+    scale: f64,
+    octaves: Octaves,
+}
+
+impl Fbm {
+    pub fn new(scale: f64) -> Self {
+        Fbm { noise: Perlin::default(), scale, octaves: Octaves::default() }
+    }
+
+    #[allow(unused)]
+    pub fn with_octaves(mut self, octaves: Octaves) -> Self {
+        self.octaves = octaves;
+        self
+    }
+}
+
+impl Texture for Fbm {
+    fn value(&self, _u:f64, _v:f64, p:Vec3) -> Color {
+        let o = self.octaves;
+        gray(0.5 + 0.5 * self.noise.fbm(p * self.scale, o.count, o.lacunarity, o.gain))
+    }
+}
+
+pub struct Ridged {
+    noise: Perlin,
+    scale: f64,
+    octaves: Octaves,
+}
+
+impl Ridged {
+    pub fn new(scale: f64) -> Self {
+        Ridged { noise: Perlin::default(), scale, octaves: Octaves::default() }
+    }
+
+    #[allow(unused)]
+    pub fn with_octaves(mut self, octaves: Octaves) -> Self {
+        self.octaves = octaves;
+        self
+    }
+}
+
+impl Texture for Ridged {
+    fn value(&self, _u:f64, _v:f64, p:Vec3) -> Color {
+        let o = self.octaves;
+        gray(self.noise.ridged(p * self.scale, o.count, o.lacunarity, o.gain))
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum CellFeature {
+    Nearest, // Dark spots at the feature points, brightening towards the cell borders
+    SecondNearest, // Rounded, pillowy cells
+    Edges, // Thin dark lines along the borders between cells, like cracks or scales
+}
+
+pub struct Cellular {
+    noise: Worley,
+    scale: f64,
+    feature: CellFeature,
+}
+
+impl Cellular {
+    pub fn new(scale: f64, feature: CellFeature) -> Self {
+        Cellular { noise: Worley::default(), scale, feature }
+    }
+}
+
+impl Texture for Cellular {
+    fn value(&self, _u:f64, _v:f64, p:Vec3) -> Color {
+        let (nearest, second) = self.noise.distances(p * self.scale);
+        gray(match self.feature {
+            CellFeature::Nearest => nearest,
+            CellFeature::SecondNearest => second - SECOND_NEAREST_MIN,
+            CellFeature::Edges => (second - nearest) / EDGE_WIDTH,
+        })
+    }
+}
+
+pub struct Wood {
+    // Concentric growth rings around the y axis, pushed off round by noise.
+    noise: Perlin,
+    rings: f64, // Rings per unit of distance from the axis
+    wobble: f64, // How far, in rings, the noise displaces them
+    ramp: ColorRamp, // From early wood at 0 to late wood at 1
+}
+
+impl Wood {
+    pub fn new(rings: f64, wobble: f64, ramp: ColorRamp) -> Self {
+        Wood { noise: Perlin::default(), rings, wobble, ramp }
+    }
+}
+
+impl Texture for Wood {
+    fn value(&self, _u:f64, _v:f64, p:Vec3) -> Color {
+        // The noise is stretched along the axis, so the rings waver like grain, not blobs.
+        let q = Vec3::new(p.x * self.rings, p.y * self.rings * 0.15, p.z * self.rings);
+        let r = p.x.hypot(p.z) * self.rings + self.wobble * self.noise.fbm(q * 0.5, 4, 2.0, 0.5);
+        // Each ring grows light in spring and darkens sharply towards autumn.
+        self.ramp.eval(r.rem_euclid(1.0).powi(3))
+    }
+}
+
+pub struct Marble {
+    // Veins along planes of constant z, folded by turbulence.
+    noise: Perlin,
+    scale: f64,
+    turbulence: f64,
+    ramp: ColorRamp, // From the vein color at 0 to the stone color at 1
+}
+
+impl Marble {
+    pub fn new(scale: f64, turbulence: f64, ramp: ColorRamp) -> Self {
+        Marble { noise: Perlin::default(), scale, turbulence, ramp }
+    }
+}
+
+impl Texture for Marble {
+    fn value(&self, _u:f64, _v:f64, p:Vec3) -> Color {
+        let s = p * self.scale;
+        self.ramp.eval(0.5 * (1.0 + (s.z + self.turbulence * self.noise.turb(s, 7)).sin()))
+    }
+}
+
+pub struct DomainWarp {
+    // Looks another texture up at a point pushed around by noise (Quilez 2002), which swirls
+    // any pattern into flowing, smeared shapes.
+    texture: Arc<dyn Texture>,
+    noise: Perlin,
+    scale: f64,
+    strength: f64, // Largest displacement, in world units
+}
+
+impl DomainWarp {
+    pub fn new(texture: Arc<dyn Texture>, scale: f64, strength: f64) -> Self {
+        DomainWarp { texture, noise: Perlin::default(), scale, strength }
+    }
+
+    fn warp(&self, p: Vec3) -> Vec3 {
+        // Three decorrelated samples of the same noise give the three components.
+        let q = p * self.scale;
+        let fbm = |offset: Vec3| self.noise.fbm(q + offset, 4, 2.0, 0.5);
+        let displacement = Vec3::new(
+            fbm(Vec3::new(0.0, 0.0, 0.0)),
+            fbm(Vec3::new(5.2, 1.3, 2.8)),
+            fbm(Vec3::new(1.7, 9.2, 3.4)),
+        );
+        p + displacement * self.strength
+    }
+}
+
+impl Texture for DomainWarp {
+    fn value(&self, u:f64, v:f64, p:Vec3) -> Color {
+        self.texture.value(u, v, self.warp(p))
+    }
+
+    fn value_at(&self, rec:&HitRecord) -> Color {
+        let mut warped = rec.clone();
+        warped.p = self.warp(rec.p);
+        self.texture.value_at(&warped)
+    }
+}
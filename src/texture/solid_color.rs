@@ -11,6 +11,11 @@ impl SolidColor {
         SolidColor{color_value:c}
     }
 
+    pub fn from_value(v:f64) -> Self{
+        // A gray texture, for material inputs that are scalars.
+        SolidColor{color_value:Color::new(v, v, v)}
+    }
+
 /*     pub fn from(r:f64, g:f64, b:f64) -> Self{
         SolidColor{color_value:Color::new(r, g, b)}
     } */
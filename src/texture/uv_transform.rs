@@ -0,0 +1,33 @@
+#[derive(Clone, Copy)]
+pub struct UvTransform {
+    // Applied to texture coordinates before lookup: rotation about the center of the texture,
+    // then scale, then offset. A scale of 2 tiles the texture twice across the surface.
+    pub scale: (f64, f64),
+    pub offset: (f64, f64),
+    pub rotation: f64, // In degrees, counterclockwise
+}
+
+impl UvTransform {
+    pub fn new(scale: (f64, f64), offset: (f64, f64), rotation: f64) -> Self {
+        UvTransform{scale, offset, rotation}
+    }
+
+    pub fn apply(&self, u: f64, v: f64) -> (f64, f64) {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let (du, dv) = (u - 0.5, v - 0.5);
+        let (ru, rv) = (cos * du - sin * dv + 0.5, sin * du + cos * dv + 0.5);
+        (ru * self.scale.0 + self.offset.0, rv * self.scale.1 + self.offset.1)
+    }
+
+    pub fn apply_vector(&self, du: f64, dv: f64) -> (f64, f64) {
+        // Transform a change in texture coordinates, which ignores the offset.
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        ((cos * du - sin * dv) * self.scale.0, (sin * du + cos * dv) * self.scale.1)
+    }
+}
+
+impl Default for UvTransform {
+    fn default() -> Self {
+        UvTransform{scale: (1.0, 1.0), offset: (0.0, 0.0), rotation: 0.0}
+    }
+}
@@ -0,0 +1,181 @@
+use std::fs;
+
+use super::Texture;
+use crate::hittable::aabb::Aabb;
+use crate::utility::{vec3::Vec3, color::Color};
+
+// Voxel files start with a one-line ASCII header followed by little-endian f32 densities.
+//
+// Dense:  "dense <nx> <ny> <nz>\n", then nx*ny*nz values with x varying fastest, then y, then z.
+// Sparse: "sparse <nx> <ny> <nz> <brick>\n", then any number of bricks, each three u32 brick
+//         coordinates followed by brick^3 values in the same order. Voxels in bricks that are
+//         never written are empty, and values that fall outside the grid are ignored.
+
+pub struct VoxelGrid {
+    nx: usize,
+    ny: usize,
+    nz: usize,
+    data: Vec<f64>, // x varies fastest, then y, then z
+    bounds: Aabb,
+    max_value: f64,
+}
+
+impl VoxelGrid {
+    pub fn new(nx:usize, ny:usize, nz:usize, data:Vec<f64>, bounds:Aabb) -> Self {
+        assert_eq!(data.len(), nx * ny * nz, "voxel grid needs nx * ny * nz values");
+        let max_value = data.iter().cloned().fold(0.0, f64::max);
+        VoxelGrid{nx, ny, nz, data, bounds, max_value}
+    }
+
+    pub fn from_fn(nx:usize, ny:usize, nz:usize, bounds:Aabb, f: impl Fn(Vec3) -> f64) -> Self {
+        // Fill the grid by evaluating f at each voxel center, given in [0,1] grid coordinates.
+        let mut data = Vec::with_capacity(nx * ny * nz);
+        for k in 0..nz {
+            for j in 0..ny {
+                for i in 0..nx {
+                    data.push(f(Vec3::new(
+                        (i as f64 + 0.5) / nx as f64,
+                        (j as f64 + 0.5) / ny as f64,
+                        (k as f64 + 0.5) / nz as f64,
+                    )));
+                }
+            }
+        }
+        VoxelGrid::new(nx, ny, nz, data, bounds)
+    }
+
+    pub fn from_file(filename:&str, bounds:Aabb) -> Self {
+        // The grid is stretched to fill bounds, which is in the medium's own object space.
+        // Place it in the world by wrapping the medium in Translate/RotateY.
+        match VoxelGrid::load(filename, bounds) {
+            Ok(grid) => grid,
+            Err(e) => panic!("Could not read {filename} into voxel grid: {e}")
+        }
+    }
+
+    pub fn load(filename:&str, bounds:Aabb) -> Result<Self, String> {
+        let bytes = fs::read(filename).map_err(|e| e.to_string())?;
+        let (nx, ny, nz, data) = parse_voxels(&bytes)?;
+        Ok(VoxelGrid::new(nx, ny, nz, data, bounds))
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        self.bounds
+    }
+
+    #[allow(unused)]
+    pub fn max_value(&self) -> f64 {
+        self.max_value
+    }
+
+    fn voxel(&self, i:usize, j:usize, k:usize) -> f64 {
+        self.data[(k * self.ny + j) * self.nx + i]
+    }
+
+    pub fn lookup(&self, p: Vec3) -> f64 {
+        // Trilinear interpolation between voxel centers. Points outside the grid are empty.
+        let dims = [self.nx, self.ny, self.nz];
+        let mut base = [0usize; 3];
+        let mut frac = [0.0; 3];
+        for a in 0..3 {
+            let axis = self.bounds.axis(a);
+            if !axis.contains(p[a]) || dims[a] == 0 {
+                return 0.0
+            }
+            let x = ((p[a] - axis.min) / axis.size() * dims[a] as f64 - 0.5).clamp(0.0, (dims[a] - 1) as f64);
+            base[a] = (x.floor() as usize).min(dims[a].saturating_sub(2));
+            frac[a] = x - base[a] as f64;
+        }
+
+        let mut accum = 0.0;
+        for (di, dj, dk) in (0..8).map(|c| (c & 1, (c >> 1) & 1, (c >> 2) & 1)) {
+            let i = (base[0] + di).min(self.nx - 1);
+            let j = (base[1] + dj).min(self.ny - 1);
+            let k = (base[2] + dk).min(self.nz - 1);
+            let w = (if di == 1 {frac[0]} else {1.0 - frac[0]})
+                * (if dj == 1 {frac[1]} else {1.0 - frac[1]})
+                * (if dk == 1 {frac[2]} else {1.0 - frac[2]});
+            accum += w * self.voxel(i, j, k);
+        }
+        accum
+    }
+}
+
+impl Texture for VoxelGrid {
+    fn value(&self, _u:f64, _v:f64, p:Vec3) -> Color {
+        // Normalized so the densest voxel reads as 1.
+        let v = if self.max_value > 0.0 {self.lookup(p) / self.max_value} else {0.0};
+        Color::new(v, v, v)
+    }
+}
+
+fn parse_voxels(bytes: &[u8]) -> Result<(usize, usize, usize, Vec<f64>), String> {
+    let header_end = bytes.iter().position(|&b| b == b'\n').ok_or("missing header")?;
+    let header = std::str::from_utf8(&bytes[..header_end]).map_err(|_| "header is not text")?;
+    let mut fields = header.split_whitespace();
+    let kind = fields.next().ok_or("empty header")?;
+    let numbers = fields
+        .map(|f| f.parse::<usize>().map_err(|_| format!("bad header field '{f}'")))
+        .collect::<Result<Vec<_>, _>>()?;
+    let body = &bytes[header_end + 1..];
+
+    let f32_at = |i: usize| f32::from_le_bytes(body[4*i..4*i + 4].try_into().unwrap()) as f64;
+    let u32_at = |i: usize| u32::from_le_bytes(body[4*i..4*i + 4].try_into().unwrap()) as usize;
+    let words = body.len() / 4;
+
+    // The sizes come from the file, so guard against products that overflow or grids too
+    // large to allocate.
+    let voxel_count = |nx: usize, ny: usize, nz: usize| {
+        nx.checked_mul(ny).and_then(|n| n.checked_mul(nz)).ok_or(format!("grid {nx}x{ny}x{nz} is too large"))
+    };
+
+    match (kind, numbers.as_slice()) {
+        ("dense", &[nx, ny, nz]) => {
+            let count = voxel_count(nx, ny, nz)?;
+            if words < count {
+                return Err(format!("expected {count} values, found {words}"))
+            }
+            Ok((nx, ny, nz, (0..count).map(f32_at).collect()))
+        }
+        ("sparse", &[nx, ny, nz, brick]) => {
+            if brick == 0 {
+                return Err("brick size must be positive".to_string())
+            }
+            let record = brick.checked_mul(brick)
+                .and_then(|n| n.checked_mul(brick))
+                .and_then(|n| n.checked_add(3))
+                .ok_or(format!("brick size {brick} is too large"))?;
+            if !words.is_multiple_of(record) {
+                return Err(format!("sparse data is not a whole number of {brick}^3 bricks"))
+            }
+
+            // Sparse files can describe a much larger grid than they hold, so its size cannot
+            // be checked against the file length; ask for the memory instead of aborting.
+            let count = voxel_count(nx, ny, nz)?;
+            let mut data = Vec::new();
+            data.try_reserve_exact(count).map_err(|_| format!("grid {nx}x{ny}x{nz} is too large"))?;
+            data.resize(count, 0.0);
+
+            for start in (0..words).step_by(record) {
+                let brick_index = [u32_at(start), u32_at(start + 1), u32_at(start + 2)];
+                let origin = brick_index.map(|b| b.saturating_mul(brick));
+                if origin[0] >= nx || origin[1] >= ny || origin[2] >= nz {
+                    return Err(format!("brick {brick_index:?} lies outside the grid"))
+                }
+                let mut n = start + 3;
+                for k in origin[2]..origin[2] + brick {
+                    for j in origin[1]..origin[1] + brick {
+                        for i in origin[0]..origin[0] + brick {
+                            if i < nx && j < ny && k < nz {
+                                data[(k * ny + j) * nx + i] = f32_at(n);
+                            }
+                            n += 1;
+                        }
+                    }
+                }
+            }
+            Ok((nx, ny, nz, data))
+        }
+        _ => Err(format!("unrecognized header '{header}'")),
+    }
+}
@@ -3,6 +3,9 @@ pub mod color;
 pub mod vec3;
 pub mod ray;
 pub mod rand;
+pub mod onb;
+pub mod spectrum;
+pub mod distribution;
 
 
 pub const INFINITY:f64 = f64::INFINITY;
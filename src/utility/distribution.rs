@@ -0,0 +1,73 @@
+pub struct Distribution1D {
+    // Piecewise-constant density over [0,1) with one bin per function value.
+    func: Vec<f64>,
+    cdf: Vec<f64>,
+    integral: f64,
+}
+
+impl Distribution1D {
+    pub fn new(func: Vec<f64>) -> Self {
+        let n = func.len();
+        let mut cdf = vec![0.0; n + 1];
+        for i in 0..n {
+            cdf[i + 1] = cdf[i] + func[i].max(0.0) / n as f64;
+        }
+        let integral = cdf[n];
+
+        // A function that is zero everywhere falls back to sampling uniformly.
+        for (i, c) in cdf.iter_mut().enumerate() {
+            *c = if integral > 0.0 {*c / integral} else {i as f64 / n as f64};
+        }
+
+        Distribution1D{func, cdf, integral}
+    }
+
+    pub fn integral(&self) -> f64 {
+        self.integral
+    }
+
+    fn bin(&self, x: f64) -> usize {
+        ((x * self.func.len() as f64) as usize).min(self.func.len() - 1)
+    }
+
+    pub fn pdf(&self, x: f64) -> f64 {
+        if self.integral <= 0.0 {return 1.0}
+        self.func[self.bin(x)].max(0.0) / self.integral
+    }
+
+    pub fn sample(&self, r: f64) -> (f64, f64) {
+        // Map a uniform random number to a position in [0,1) and its density.
+        let i = self.cdf.partition_point(|&c| c <= r).saturating_sub(1).min(self.func.len() - 1);
+        let width = self.cdf[i + 1] - self.cdf[i];
+        let offset = if width > 0.0 {(r - self.cdf[i]) / width} else {0.0};
+        let x = (i as f64 + offset) / self.func.len() as f64;
+        (x, self.pdf(x))
+    }
+}
+
+pub struct Distribution2D {
+    // Samples (u, v) in proportion to a tabulated function: v from the marginal density of
+    // the rows, then u from the chosen row.
+    rows: Vec<Distribution1D>,
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    pub fn new(func: &[f64], nu: usize, nv: usize) -> Self {
+        // func holds nv rows of nu values.
+        let rows: Vec<Distribution1D> = func.chunks(nu).take(nv).map(|row| Distribution1D::new(row.to_vec())).collect();
+        let marginal = Distribution1D::new(rows.iter().map(|r| r.integral()).collect());
+        Distribution2D{rows, marginal}
+    }
+
+    pub fn pdf(&self, u: f64, v: f64) -> f64 {
+        let row = &self.rows[self.marginal.bin(v)];
+        self.marginal.pdf(v) * row.pdf(u)
+    }
+
+    pub fn sample(&self, r1: f64, r2: f64) -> (f64, f64, f64) {
+        let (v, pdf_v) = self.marginal.sample(r2);
+        let (u, pdf_u) = self.rows[self.marginal.bin(v)].sample(r1);
+        (u, v, pdf_u * pdf_v)
+    }
+}
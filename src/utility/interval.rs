@@ -45,6 +45,7 @@ impl Interval {
     }
 
     pub const EMPTY: Interval = Interval{min:f64::INFINITY, max:f64::NEG_INFINITY};
+    #[allow(unused)]
     pub const UNIVERSE: Interval = Interval{min:f64::NEG_INFINITY, max:f64::INFINITY};
 }
 
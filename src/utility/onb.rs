@@ -0,0 +1,33 @@
+use super::vec3::{self, Vec3};
+
+pub struct Onb {
+    axis: [Vec3; 3],
+}
+
+impl Onb {
+    pub fn build_from_w(w: Vec3) -> Self {
+        // Build an orthonormal basis whose w axis points along the given vector.
+        let unit_w = vec3::unit_vector(w);
+        let a = if unit_w.x.abs() > 0.9 {Vec3::new(0.0, 1.0, 0.0)} else {Vec3::new(1.0, 0.0, 0.0)};
+        let v = vec3::unit_vector(unit_w.cross(a));
+        let u = unit_w.cross(v);
+        Onb { axis: [u, v, unit_w] }
+    }
+
+    pub fn u(&self) -> Vec3 {
+        self.axis[0]
+    }
+
+    pub fn v(&self) -> Vec3 {
+        self.axis[1]
+    }
+
+    pub fn w(&self) -> Vec3 {
+        self.axis[2]
+    }
+
+    pub fn local(&self, a: f64, b: f64, c: f64) -> Vec3 {
+        // Express the local coordinates (a, b, c) in world space.
+        self.u() * a + self.v() * b + self.w() * c
+    }
+}
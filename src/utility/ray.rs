@@ -4,11 +4,31 @@ pub struct Ray {
     origin: Vec3,
     direction: Vec3,
     tm: f64,
+    differentials: Option<Differentials>,
+}
+
+// Two rays offset by one pixel horizontally (x) and vertically (y) from a main ray, which
+// tell how large a patch of the scene the ray stands for.
+#[derive(Debug, Clone, Copy)]
+pub struct Differentials {
+    pub rx_origin: Vec3,
+    pub rx_direction: Vec3,
+    pub ry_origin: Vec3,
+    pub ry_direction: Vec3,
 }
 
 impl Ray {
     pub fn new_timed(origin: Vec3, direction: Vec3, tm: f64) -> Self {
-        Ray{origin, direction, tm}
+        Ray{origin, direction, tm, differentials: None}
+    }
+
+    pub fn with_differentials(mut self, differentials: Option<Differentials>) -> Self {
+        self.differentials = differentials;
+        self
+    }
+
+    pub fn differentials(&self) -> Option<Differentials> {
+        self.differentials
     }
 
     pub fn origin(&self) -> Vec3 {
@@ -0,0 +1,179 @@
+use std::sync::OnceLock;
+
+use super::{rand, color::Color, vec3::Vec3, PI};
+
+// Visible range covered by the RGB to spectrum tables, in nanometers.
+pub const LAMBDA_MIN: f64 = 380.0;
+pub const LAMBDA_MAX: f64 = 720.0;
+
+// Sodium D line, where a single "index of refraction" is usually quoted.
+pub const LAMBDA_D: f64 = 589.3;
+
+const SMITS_BINS: usize = 10;
+
+// Smits' basis spectra for converting RGB reflectances, sampled over [LAMBDA_MIN, LAMBDA_MAX].
+const SMITS_WHITE:   [f64; SMITS_BINS] = [1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000];
+const SMITS_CYAN:    [f64; SMITS_BINS] = [0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000];
+const SMITS_MAGENTA: [f64; SMITS_BINS] = [1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959];
+const SMITS_YELLOW:  [f64; SMITS_BINS] = [0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840];
+const SMITS_RED:     [f64; SMITS_BINS] = [0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149];
+const SMITS_GREEN:   [f64; SMITS_BINS] = [0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025];
+const SMITS_BLUE:    [f64; SMITS_BINS] = [1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496];
+
+#[derive(Clone, Copy)]
+pub struct Wavelengths {
+    // Hero wavelength in x, plus two companions spread evenly across the visible range.
+    lambda: Vec3,
+    secondary_terminated: bool,
+}
+
+impl Wavelengths {
+    pub fn sample() -> Self {
+        let u = rand::random_double();
+        let range = LAMBDA_MAX - LAMBDA_MIN;
+        let at = |offset: f64| LAMBDA_MIN + range * (u + offset).fract();
+        Wavelengths {
+            lambda: Vec3::new(at(0.0), at(1.0 / 3.0), at(2.0 / 3.0)),
+            secondary_terminated: false,
+        }
+    }
+
+    pub fn hero(&self) -> f64 {
+        self.lambda.x
+    }
+
+    pub fn terminate_secondary(&self) -> (Wavelengths, Vec3) {
+        // Wavelength dependent scattering sends each wavelength in a different direction, so only
+        // the hero wavelength can follow the path. Its weight is scaled up to keep the estimate
+        // unbiased. Returns the updated wavelengths and the weight to apply to the throughput.
+        if self.secondary_terminated {
+            return (*self, Vec3::new(1.0, 0.0, 0.0))
+        }
+        let w = Wavelengths { lambda: self.lambda, secondary_terminated: true };
+        (w, Vec3::new(3.0, 0.0, 0.0))
+    }
+
+    pub fn sample_rgb(&self, c: Color) -> Vec3 {
+        // Values of the spectrum matching the RGB color at each of the sampled wavelengths.
+        Vec3::new(
+            rgb_to_spectrum(c, self.lambda.x),
+            rgb_to_spectrum(c, self.lambda.y),
+            rgb_to_spectrum(c, self.lambda.z),
+        )
+    }
+
+    pub fn to_rgb(self, radiance: Vec3) -> Color {
+        // Monte Carlo estimate of the XYZ integral from the three wavelength samples, converted to
+        // linear sRGB and white balanced so a flat unit spectrum maps to (1, 1, 1).
+        let pdf = 1.0 / (LAMBDA_MAX - LAMBDA_MIN);
+        let xyz = (cie_xyz(self.lambda.x) * radiance.x
+            + cie_xyz(self.lambda.y) * radiance.y
+            + cie_xyz(self.lambda.z) * radiance.z) / (3.0 * pdf);
+
+        let white = *SPECTRAL_WHITE.get_or_init(|| {
+            let steps = (LAMBDA_MAX - LAMBDA_MIN) as i32;
+            let xyz = (0..steps).map(|i| cie_xyz(LAMBDA_MIN + i as f64 + 0.5)).sum();
+            xyz_to_linear_srgb(xyz)
+        });
+
+        let rgb = xyz_to_linear_srgb(xyz);
+        Color::new(rgb.x / white.x, rgb.y / white.y, rgb.z / white.z)
+    }
+}
+
+static SPECTRAL_WHITE: OnceLock<Color> = OnceLock::new();
+
+pub fn rgb_to_spectrum(c: Color, lambda: f64) -> f64 {
+    // Smits 1999: build the spectrum from white plus the two basis spectra needed to reach the
+    // remaining components.
+    let bin = (((lambda - LAMBDA_MIN) / (LAMBDA_MAX - LAMBDA_MIN) * SMITS_BINS as f64) as usize).min(SMITS_BINS - 1);
+    let (r, g, b) = (c.x, c.y, c.z);
+
+    if r <= g && r <= b {
+        let base = r * SMITS_WHITE[bin];
+        if g <= b {
+            base + (g - r) * SMITS_CYAN[bin] + (b - g) * SMITS_BLUE[bin]
+        } else {
+            base + (b - r) * SMITS_CYAN[bin] + (g - b) * SMITS_GREEN[bin]
+        }
+    } else if g <= r && g <= b {
+        let base = g * SMITS_WHITE[bin];
+        if r <= b {
+            base + (r - g) * SMITS_MAGENTA[bin] + (b - r) * SMITS_BLUE[bin]
+        } else {
+            base + (b - g) * SMITS_MAGENTA[bin] + (r - b) * SMITS_RED[bin]
+        }
+    } else {
+        let base = b * SMITS_WHITE[bin];
+        if r <= g {
+            base + (r - b) * SMITS_YELLOW[bin] + (g - r) * SMITS_GREEN[bin]
+        } else {
+            base + (g - b) * SMITS_YELLOW[bin] + (r - g) * SMITS_RED[bin]
+        }
+    }
+}
+
+pub fn cie_xyz(lambda: f64) -> Vec3 {
+    // Analytic multi-lobe fit of the CIE 1931 color matching functions (Wyman, Sloan and
+    // Shirley 2013).
+    let g = |mu: f64, s1: f64, s2: f64| {
+        let t = (lambda - mu) / if lambda < mu {s1} else {s2};
+        (-0.5 * t * t).exp()
+    };
+
+    Vec3::new(
+        1.056 * g(599.8, 37.9, 31.0) + 0.362 * g(442.0, 16.0, 26.7) - 0.065 * g(501.1, 20.4, 26.2),
+        0.821 * g(568.8, 46.9, 40.5) + 0.286 * g(530.9, 16.3, 31.1),
+        1.217 * g(437.0, 11.8, 36.0) + 0.681 * g(459.0, 26.0, 13.8),
+    )
+}
+
+pub fn xyz_to_linear_srgb(xyz: Vec3) -> Color {
+    Color::new(
+        Vec3::new( 3.2404542, -1.5371385, -0.4985314).dot(xyz),
+        Vec3::new(-0.9692660,  1.8760108,  0.0415560).dot(xyz),
+        Vec3::new( 0.0556434, -0.2040259,  1.0572252).dot(xyz),
+    )
+}
+
+pub fn planck(lambda: f64, kelvin: f64) -> f64 {
+    // Spectral radiance of a blackbody, in W / (m^2 sr nm), at a wavelength in nanometers.
+    const H: f64 = 6.62607015e-34;
+    const C: f64 = 2.99792458e8;
+    const K_B: f64 = 1.380649e-23;
+
+    let l = lambda * 1e-9;
+    2.0 * H * C * C / (l.powi(5) * ((H * C / (l * K_B * kelvin)).exp() - 1.0)) * 1e-9
+}
+
+pub fn blackbody_color(kelvin: f64) -> Color {
+    // Linear sRGB color of a blackbody, scaled to unit luminance. Very warm temperatures fall
+    // outside the sRGB gamut, so negative components are clipped.
+    let mut xyz = Vec3::default();
+    let mut lambda = 360.0;
+    while lambda <= 830.0 {
+        xyz = xyz + cie_xyz(lambda) * planck(lambda, kelvin);
+        lambda += 5.0;
+    }
+    if xyz.y <= 0.0 {
+        return Color::default()
+    }
+
+    let rgb = xyz_to_linear_srgb(xyz / xyz.y);
+    Color::new(rgb.x.max(0.0), rgb.y.max(0.0), rgb.z.max(0.0))
+}
+
+pub fn luminous_efficiency(kelvin: f64) -> f64 {
+    // Fraction of a blackbody's radiated power that counts as luminance: the photopic response
+    // integrated over its spectrum, divided by the Stefan-Boltzmann total. Multiply by
+    // 683 lm/W for the luminous efficacy.
+    const STEFAN_BOLTZMANN: f64 = 5.670374419e-8;
+
+    let mut luminance = 0.0;
+    let mut lambda = 360.0;
+    while lambda <= 830.0 {
+        luminance += cie_xyz(lambda).y * planck(lambda, kelvin) * 5.0;
+        lambda += 5.0;
+    }
+    luminance / (STEFAN_BOLTZMANN * kelvin.powi(4) / PI)
+}